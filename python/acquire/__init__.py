@@ -192,8 +192,12 @@ def gui(
         def next_frame() -> Optional[npt.NDArray[Any]]:
             """Get the next frame from the current stream."""
             if nframes[stream_id] < p.video[stream_id].max_frame_count:
-                with runtime.get_available_data(stream_id) as packet:
+                with runtime.get_available_data(
+                    stream_id, min_frames=1, timeout_ms=30
+                ) as packet:
                     n = packet.get_frame_count()
+                    if n == 0:
+                        return None
                     nframes[stream_id] += n
                     logging.info(
                         f"[stream {stream_id}] frame count: {nframes}"
@@ -209,9 +213,10 @@ def gui(
             clock = time.time()
             if (frame := next_frame()) is not None:
                 yield frame, stream_id
+                # Keep the preview to ~33 updates per second.
+                elapsed = time.time() - clock
+                time.sleep(max(0, 0.03 - elapsed))
             stream_id = (stream_id + 1) % stream_count
-            elapsed = time.time() - clock
-            time.sleep(max(0, 0.03 - elapsed))
         logging.info("stopping")
 
         counts, bins = histogram(update_times)
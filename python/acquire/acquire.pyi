@@ -1,17 +1,53 @@
+import os
+from datetime import datetime
 from typing import (
     Any,
+    AsyncIterator,
+    Awaitable,
+    Callable,
     ClassVar,
     Dict,
     Iterator,
     List,
+    Literal,
     Optional,
     Tuple,
+    Union,
     final,
     overload,
 )
 
 from numpy.typing import NDArray
 
+class AcquireError(RuntimeError):
+    """Raised when a call into the core runtime fails.
+
+    The subclasses `ConfigurationError`, `DeviceNotFoundError`, `StateError`,
+    `StorageError` and `AcquireTimeoutError` indicate what kind of failure
+    occurred.
+
+    Attributes:
+        api:
+            The name of the core API function that failed.
+        stream_id:
+            The ID of the video stream involved, if any.
+        status_code:
+            The status code returned by the core API function, if any.
+        messages:
+            The most recent error messages reported by the core runtime.
+    """
+
+    api: str
+    stream_id: Optional[int]
+    status_code: Optional[int]
+    messages: List[str]
+
+class AcquireTimeoutError(AcquireError, TimeoutError):
+    """Raised when the runtime did not respond in time.
+
+    Also a builtin `TimeoutError`, so `except TimeoutError` catches it.
+    """
+
 @final
 class AvailableData:
     """The AvailableData class represents the collection of frames that have
@@ -21,8 +57,14 @@ class AvailableData:
     objects reserve space on the video queue and will eventually block camera
     acquisition to ensure no data is overwritten before it can be processed.
 
+    Attributes:
+        wait_status:
+            An instance of the `WaitStatus` class indicating why
+            `runtime.get_available_data()` stopped waiting for frames.
     """
 
+    wait_status: WaitStatus
+
     def frames(self) -> Iterator[VideoFrame]:
         """Returns an iterator over the video frames in the available data.
 
@@ -42,7 +84,80 @@ class AvailableData:
 
         """
         ...
+    def consume(self, n_frames: int) -> None:
+        """Marks the first `n_frames` frames as consumed.
+
+        Only consumed frames are released back to the runtime when the
+        context exits. The remaining frames are returned again by the next
+        call to `Runtime.get_available_data()`. Calling `consume()` again
+        replaces the previous count.
+
+        Parameters:
+            n_frames:
+                The number of frames, from the start of the available data,
+                to release.
+
+        Raises:
+            ValueError:
+                If fewer than `n_frames` frames are available.
+        """
+        ...
+    def to_array(self) -> NDArray[Any]:
+        """Copies every frame into one array.
+
+        Call `to_array()` instead of stacking the result of `data()` for each
+        frame. The frames are copied into a single allocation without holding
+        the GIL.
+
+        Returns:
+            An array with shape `(N, planes, height, width, channels)`, where
+            `N` is the number of frames. An empty `uint8` array if there are
+            no frames.
+
+        Raises:
+            ValueError:
+                If the frames have different shapes or sample types.
+        """
+        ...
+    def detach_all(self) -> List[OwnedVideoFrame]:
+        """Copies every frame so it can be used after the context exits.
+
+        Use it to hand frames to other threads or queues. The copies are made
+        into buffers that are reused once the returned frames are dropped.
+
+        Returns:
+            An `OwnedVideoFrame` for each frame, in frame order.
+        """
+        ...
+    def metadata_table(self) -> Dict[str, NDArray[Any]]:
+        """Returns the metadata of every frame as parallel arrays.
+
+        Returns:
+            A dict with the `frame_id`, `hardware` and `acq_thread` values of
+            each frame, as 1-d `uint64` arrays in frame order.
+        """
+        ...
     def __iter__(self) -> Iterator[VideoFrame]: ...
+    def __len__(self) -> int:
+        """Returns the number of frames, like `get_frame_count()`."""
+        ...
+    @overload
+    def __getitem__(self, index: int) -> VideoFrame:
+        """Returns the frame at `index`. Negative indices count from the
+        last frame.
+
+        Frames are indexed when the context is entered, so this does not
+        walk the frames before `index`.
+
+        Raises:
+            IndexError:
+                If `index` is out of range.
+        """
+        ...
+    @overload
+    def __getitem__(self, index: slice) -> List[VideoFrame]:
+        """Returns a list of the frames in the slice."""
+        ...
 
 @final
 class AvailableDataContext:
@@ -224,6 +339,111 @@ class Capabilities:
         """Returns a dictionary of the `Capabilities` object's attributes."""
         ...
 
+@final
+class ClockCorrelation:
+    """Host clock readings taken together when the runtime was started, used
+    to convert frame timestamps.
+
+    `VideoFrameTimestamps.acq_thread` counts nanoseconds on the core's clock,
+    which Python can't read. `acq_thread_ns` is read from the core's clock
+    between two readings of Python's clocks, and `monotonic_ns` and
+    `wall_clock_ns` are the midpoints of those. Hardware timestamps use
+    device-specific units and are not converted.
+
+    Attributes:
+        monotonic_ns:
+            The value of `time.monotonic_ns()` when the runtime was started.
+        wall_clock_ns:
+            The value of `time.time_ns()` at the same moment.
+        acq_thread_ns:
+            The core's clock, which `acq_thread` timestamps are read from, at
+            the same moment.
+    """
+
+    monotonic_ns: int
+    wall_clock_ns: int
+    acq_thread_ns: int
+
+    def __init__(self, *args: None, **kwargs: Any) -> None: ...
+    def seconds_since_start(self, timestamps: VideoFrameTimestamps) -> float:
+        """Returns the seconds from when the runtime was started to when the
+        frame was acquired.
+        """
+        ...
+    def to_monotonic(self, timestamps: VideoFrameTimestamps) -> float:
+        """Returns when the frame was acquired, in seconds on the
+        `time.monotonic()` clock.
+        """
+        ...
+    def to_datetime(self, timestamps: VideoFrameTimestamps) -> datetime:
+        """Returns when the frame was acquired as a timezone-aware UTC
+        `datetime`.
+        """
+        ...
+    def dict(self) -> Dict[str, Any]:
+        """Returns a dictionary of the `ClockCorrelation` object's
+        attributes.
+        """
+        ...
+
+@final
+class ConfigurationChange:
+    """The `ConfigurationChange` class represents a setting that was applied
+    with a different value than was requested, as reported by
+    `Runtime.set_configuration()`.
+
+    Attributes:
+        field:
+            The path to the setting from the `Properties`, for example
+            `"video[0].camera.settings.exposure_time_us"`.
+        requested:
+            The requested value, or `None` if it was not requested.
+        applied:
+            The applied value, or `None` if it was not applied.
+    """
+
+    field: str
+    requested: Any
+    applied: Any
+
+    def dict(self) -> Dict[str, Any]:
+        """Returns a dictionary of a `ConfigurationChange` object's
+        attributes.
+        """
+        ...
+
+class ConfigurationError(AcquireError):
+    """Raised when the runtime rejects a configuration."""
+
+@final
+class ConfigurationViolation:
+    """The `ConfigurationViolation` class represents a setting that the
+    configured devices can't honor, as found by
+    `Runtime.validate_configuration()`.
+
+    Attributes:
+        stream_id:
+            The video stream the setting belongs to.
+        field:
+            The path to the setting from the `Properties`, for example
+            `"video[0].camera.settings.exposure_time_us"`.
+        kind:
+            The `ViolationKind` describing what is wrong with the setting.
+        message:
+            A description of the problem, including the supported values.
+    """
+
+    stream_id: int
+    field: str
+    kind: ViolationKind
+    message: str
+
+    def dict(self) -> Dict[str, Any]:
+        """Returns a dictionary of a `ConfigurationViolation` object's
+        attributes.
+        """
+        ...
+
 @final
 class DeviceIdentifier:
     """Represents an identifier for a supported device, including its unique id
@@ -233,12 +453,16 @@ class DeviceIdentifier:
         id:
             A tuple of `(driver_id, device_id)` containing two Uint8 integers
             that serve to identify each driver and device uniquely for a given
-            run.
+            run. The ids are not serialized. When a configuration is applied,
+            the device is looked up again by `kind` and `name`, and the ids
+            are kept only if they still refer to a device with that name.
         kind:
             An instance of the `DeviceKind` class that represents the type or
             kind of the device.
         name:
-            A string representing the name or label of the device.
+            A string representing the name or label of the device. When
+            looking up the device, a device with exactly this name is
+            preferred; otherwise it is taken as a regular expression.
     """
 
     id: Tuple[int, int]
@@ -342,7 +566,10 @@ class DeviceManager:
         """Returns a list of all available device identifiers."""
         ...
     def select(
-        self, kind: DeviceKind, name: Optional[str] = None
+        self,
+        kind: DeviceKind,
+        name: Optional[str] = None,
+        required: bool = False,
     ) -> Optional[DeviceIdentifier]:
         """Selects a specified device.
 
@@ -354,10 +581,17 @@ class DeviceManager:
                 The type of device to select.
             name:
                 The name of the device to select. Regular expressions supported.
+            required:
+                Raise `DeviceNotFoundError` instead of returning None when no
+                device matches.
 
         Returns:
             The selected device identifier, or None if the specified device is
-            not available.
+            not available and `required` is False.
+
+        Raises:
+            DeviceNotFoundError:
+                If `required` is True and no device matches.
         """
     def select_one_of(
         self, kind: DeviceKind, names: List[str]
@@ -377,6 +611,9 @@ class DeviceManager:
             if none of the specified devices are available.
         """
 
+class DeviceNotFoundError(AcquireError):
+    """Raised when a requested device could not be found."""
+
 @final
 class DeviceState:
     """The `DeviceState` class represents the acquisition status of a device.
@@ -537,6 +774,144 @@ class Direction:
         """Checks if two Direction objects are not equal."""
         ...
 
+@final
+class FrameStream:
+    """The `FrameStream` class is an asynchronous iterator over the frames of
+    a video stream.
+
+    Returned by `runtime.stream_frames()`. A background thread waits for new
+    data and wakes the asyncio event loop only once frames are available.
+    Iteration ends once the runtime has stopped and all remaining frames have
+    been yielded. A stream created while the runtime isn't running, and
+    with no frames queued, waits for the runtime to start first.
+
+    Each `VideoFrame` is only valid until the next frame is requested, so use
+    it inside the body of the `async for` loop. Once the last frame of a
+    region of data has been yielded, the region is released, so the stream
+    can be read some other way in the meantime.
+    """
+
+    def __aiter__(self) -> AsyncIterator[VideoFrame]: ...
+    def __anext__(self) -> Awaitable[VideoFrame]: ...
+
+@final
+class FrameDataGuard:
+    """The `FrameDataGuard` class is the base object of arrays returned by
+    `VideoFrame.data(copy=False)`.
+
+    It keeps the frame in the video queue for as long as the array is alive,
+    even after the `AvailableDataContext` the frame came from has exited.
+    Reading the stream with `Runtime.get_available_data()`,
+    `Runtime.stream_frames()` or a frame callback skips past it, but neither
+    it nor the frames read after it are released to the runtime until the
+    array is gone, so the queue fills up meanwhile. Once the held frames
+    reach the end of the queue's buffer, reads return no more frames until
+    then. `Runtime.start()` raises `StateError` while such arrays are alive.
+    """
+
+@final
+class FrameShapeInfo:
+    """The `FrameShapeInfo` class describes how a frame's image data is laid
+    out in memory.
+
+    Attributes:
+        dims:
+            The `(planes, height, width, channels)` shape of the frame.
+        strides:
+            The strides of each dimension in `dims`, in samples. Rows may be
+            padded, so these need not describe a contiguous layout.
+        sample_type:
+            The `SampleType` of the frame.
+        is_packed:
+            Whether 10, 12 or 14 bit samples are packed together rather than
+            stored in 16 bits each.
+        nbytes:
+            The size of the image data in bytes.
+    """
+
+    dims: Tuple[int, int, int, int]
+    strides: Tuple[int, int, int, int]
+    sample_type: SampleType
+    is_packed: bool
+    nbytes: int
+
+    def dict(self) -> Dict[str, Any]:
+        """Returns a dictionary of a `FrameShapeInfo` object's attributes."""
+        ...
+
+@final
+class FrameIdAnomaly:
+    """The `FrameIdAnomaly` class represents a frame whose id did not
+    directly follow the id of the frame read before it.
+
+    Attributes:
+        kind:
+            The `FrameIdAnomalyKind` of the anomaly.
+        previous_frame_id:
+            The id of the frame read before this one.
+        frame_id:
+            The id of this frame.
+        missing:
+            The number of frames missing between the two. Zero unless `kind`
+            is `FrameIdAnomalyKind.Gap`.
+    """
+
+    kind: FrameIdAnomalyKind
+    previous_frame_id: int
+    frame_id: int
+    missing: int
+
+    def dict(self) -> Dict[str, Any]:
+        """Returns a dictionary of a `FrameIdAnomaly` object's attributes."""
+        ...
+
+@final
+class FrameIdAnomalyKind:
+    """The `FrameIdAnomalyKind` class represents how a frame's id departed
+    from the expected sequence.
+
+    Attributes:
+        Gap:
+            Enum-type class variable of `FrameIdAnomalyKind` that specifies
+            ids were skipped, so frames were dropped.
+        Repeat:
+            Enum-type class variable of `FrameIdAnomalyKind` that specifies
+            the id was the same as the previous frame's.
+        OutOfOrder:
+            Enum-type class variable of `FrameIdAnomalyKind` that specifies
+            the id was smaller than the previous frame's.
+    """
+
+    Gap: ClassVar[FrameIdAnomalyKind]
+    Repeat: ClassVar[FrameIdAnomalyKind]
+    OutOfOrder: ClassVar[FrameIdAnomalyKind]
+
+    def __eq__(self, other: object) -> bool:
+        """Checks if two FrameIdAnomalyKind objects are equal."""
+        ...
+    def __ge__(self, other: object) -> bool:
+        """Checks if this FrameIdAnomalyKind is greater than or equal to
+        another.
+        """
+        ...
+    def __gt__(self, other: object) -> bool:
+        """Checks if this FrameIdAnomalyKind is greater than another."""
+        ...
+    def __int__(self) -> int:
+        """Converts the FrameIdAnomalyKind to an integer."""
+        ...
+    def __le__(self, other: object) -> bool:
+        """Checks if this FrameIdAnomalyKind is less than or equal to
+        another.
+        """
+        ...
+    def __lt__(self, other: object) -> bool:
+        """Checks if this FrameIdAnomalyKind is less than another."""
+        ...
+    def __ne__(self, other: object) -> bool:
+        """Checks if two FrameIdAnomalyKind objects are not equal."""
+        ...
+
 @final
 class InputTriggers:
     """The `InputTriggers` class represents input triggers for a camera device.
@@ -561,6 +936,75 @@ class InputTriggers:
         """Returns a dictionary of a `InputTriggers` object's attributes."""
         ...
 
+@final
+class LogLevel:
+    """The `LogLevel` class represents the severity of a message reported by
+    the core runtime.
+
+    Attributes:
+        Debug:
+            Enum-type class variable of `LogLevel` that specifies an
+            informational message.
+        Error:
+            Enum-type class variable of `LogLevel` that specifies an error.
+    """
+
+    Debug: ClassVar[LogLevel]
+    Error: ClassVar[LogLevel]
+
+    def __eq__(self, other: object) -> bool:
+        """Checks if two LogLevel objects are equal."""
+        ...
+    def __ge__(self, other: object) -> bool:
+        """Checks if this LogLevel is greater than or equal to another."""
+        ...
+    def __gt__(self, other: object) -> bool:
+        """Checks if this LogLevel is greater than another."""
+        ...
+    def __int__(self) -> int:
+        """Converts the LogLevel to an integer."""
+        ...
+    def __le__(self, other: object) -> bool:
+        """Checks if this LogLevel is less than or equal to another."""
+        ...
+    def __lt__(self, other: object) -> bool:
+        """Checks if this LogLevel is less than another."""
+        ...
+    def __ne__(self, other: object) -> bool:
+        """Checks if two LogLevel objects are not equal."""
+        ...
+
+@final
+class LogRecord:
+    """The `LogRecord` class represents a message reported by the core
+    runtime or one of its drivers.
+
+    Attributes:
+        level:
+            The `LogLevel` of the message.
+        file:
+            The source file the message was reported from.
+        line:
+            The line in `file` the message was reported from.
+        function:
+            The function the message was reported from.
+        message:
+            The text of the message.
+        timestamp:
+            When the message was received, in seconds since the unix epoch.
+    """
+
+    level: LogLevel
+    file: str
+    line: int
+    function: str
+    message: str
+    timestamp: float
+
+    def dict(self) -> Dict[str, Any]:
+        """Returns a dictionary of a `LogRecord` object's attributes."""
+        ...
+
 @final
 class OffsetCapabilities:
     x: Property
@@ -592,6 +1036,49 @@ class OutputTriggers:
     def dict(self) -> Dict[str, Any]:
         """Returns a dictionary of the `OutputTriggers` object's attributes."""
 
+@final
+class OwnedVideoFrame:
+    """A copy of a `VideoFrame` that stays valid after the
+    `AvailableDataContext` it came from has exited.
+
+    Created by `VideoFrame.detach()` and `AvailableData.detach_all()`. It has
+    the same methods as `VideoFrame`. Arrays from `data(copy=False)` and
+    DLPack tensors keep the frame alive, and stay valid for as long as they
+    are referenced.
+    """
+
+    def data(self, copy: bool = True, raw: bool = False) -> NDArray[Any]:
+        """Returns the data of the frame as an NDArray.
+
+        See `VideoFrame.data()`. When `copy` is `False`, the array's `base`
+        is the `OwnedVideoFrame`.
+        """
+        ...
+    def __dlpack__(
+        self,
+        *,
+        stream: Optional[Any] = None,
+        max_version: Optional[Tuple[int, int]] = None,
+        dl_device: Optional[Tuple[int, int]] = None,
+        copy: Optional[bool] = None,
+    ) -> Any:
+        """Exports the frame's image data as a DLPack capsule.
+
+        See `VideoFrame.__dlpack__()`.
+        """
+        ...
+    def __dlpack_device__(self) -> Tuple[int, int]:
+        """Returns the DLPack device of the frame's memory, which is always
+        the CPU.
+        """
+        ...
+    def shape_info(self) -> FrameShapeInfo:
+        """Returns how the frame's image data is laid out in memory."""
+        ...
+    def metadata(self) -> VideoFrameMetadata:
+        """Returns the metadata associated with the frame."""
+        ...
+
 @final
 class PID:
     """The `PID` class represents proportional-integral-derivative (PID) values.
@@ -632,12 +1119,117 @@ class Properties:
 
     video: Tuple[VideoStream, VideoStream]
 
-    def __init__(self, *args: None, **kwargs: Any) -> None:
-        """Initializes a `Properties` object with optional arguments."""
+    def __init__(
+        self, *args: None, strict: bool = True, **kwargs: Any
+    ) -> None:
+        """Initializes a `Properties` object with optional arguments.
+
+        The constructors of the other settings classes, e.g. `Trigger`, check
+        their arguments the same way and take the same `strict` keyword.
+
+        Parameters:
+            strict:
+                When `False`, keys that don't name a setting are logged and
+                ignored instead of raising.
+
+        Raises:
+            ValueError: If a key, at any depth, doesn't name a setting or a
+                value has the wrong type. The message gives the path to the
+                setting, e.g. `video[0].camera.settings.exposure_time_ms`.
+        """
         ...
     def dict(self) -> Dict[str, Any]:
         """Returns a dictionary of the `Properties` attributes."""
         ...
+    def get_path(self, path: str) -> Any:
+        """Returns the setting at `path`.
+
+        Paths separate attributes with `.` and index sequences with `[i]`,
+        e.g. `video[0].camera.settings.shape[0]`.
+
+        Raises:
+            ValueError: If `path` doesn't name a setting.
+        """
+        ...
+    def set_path(self, path: str, value: Any) -> None:
+        """Sets the setting at `path`. See `get_path()`.
+
+        A dict `value` is merged into the settings at `path` rather than
+        replacing them.
+
+        Raises:
+            ValueError: If `path` doesn't name a setting.
+            TypeError: If `value` has the wrong type for the setting.
+        """
+        ...
+    def to_json(self) -> str:
+        """Serializes the properties to JSON, tagged with a schema version."""
+        ...
+    @staticmethod
+    def from_json(text: str, strict: bool = True) -> Properties:
+        """Parses properties from JSON.
+
+        Accepts the output of `to_json()` as well as an unversioned
+        `json.dumps(properties.dict())`, upgrading older layouts.
+
+        Parameters:
+            text:
+                The JSON to parse.
+            strict:
+                When `False`, keys that don't name a setting are logged and
+                ignored instead of raising.
+
+        Raises:
+            ValueError: If the text can't be parsed, has unknown or mistyped
+                settings, or was written by a newer version of `acquire`.
+        """
+        ...
+    def to_toml(self) -> str:
+        """Serializes the properties to TOML, tagged with a schema version.
+
+        Unset optional settings are omitted, since TOML has no null.
+
+        Raises:
+            ValueError:
+                If a setting is an integer larger than TOML can hold, i.e.
+                above `2**63 - 1`.
+        """
+        ...
+    @staticmethod
+    def from_toml(text: str, strict: bool = True) -> Properties:
+        """Parses properties from TOML. See `from_json()`."""
+        ...
+    def save(self, path: Union[str, os.PathLike[str]]) -> None:
+        """Writes the properties to `path`.
+
+        The file is TOML if `path` ends in `.toml` and JSON otherwise. See
+        `to_toml()` for the integers TOML can't hold.
+        """
+        ...
+    @staticmethod
+    def load(
+        path: Union[str, os.PathLike[str]],
+        runtime: Optional[Runtime] = None,
+        strict: bool = True,
+    ) -> Properties:
+        """Reads properties written by `save()`.
+
+        Parameters:
+            path:
+                The file to read.
+            runtime:
+                When given, each device is looked up by kind and name in
+                this runtime and its `id` is filled in.
+            strict:
+                When `False`, keys that don't name a setting are logged and
+                ignored instead of raising.
+
+        Raises:
+            OSError: If the file can't be read.
+            ValueError: If the file can't be parsed.
+            DeviceNotFoundError: If `runtime` has no matching device.
+        """
+        ...
 
 @final
 class Property:
@@ -724,22 +1316,176 @@ class Runtime:
         with this `Runtime` instance.
         """
         ...
-    def get_available_data(self, stream_id: int) -> AvailableDataContext:
+    def get_available_data(
+        self,
+        stream_id: int,
+        min_frames: int = 0,
+        timeout_ms: Optional[int] = None,
+        peek: bool = False,
+    ) -> AvailableDataContext:
         """Returns the AvailableDataContext instance for the given stream ID.
 
         Call `get_available_data` with a specific `stream_id`, 0 or 1, to
         return the context manager, `AvailableDataContext`, associated with the
         1st or 2nd video source, respectively.
 
+        When `min_frames` is greater than zero, entering the context blocks,
+        with the GIL released, until at least `min_frames` frames are
+        available, `timeout_ms` milliseconds have elapsed, or the runtime is
+        no longer `Running`. The `wait_status` attribute of the returned
+        `AvailableData` says which of these happened.
+
+        By default, all frames are released back to the runtime when the
+        context exits. With `peek=True`, or after calling
+        `AvailableData.consume()`, only the consumed frames are released and
+        the rest are returned again by the next call.
+
         Parameters:
             stream_id:
                 The ID of the stream for which available data is requested.
+            min_frames:
+                The minimum number of frames to wait for. The default, 0,
+                does not wait.
+            timeout_ms:
+                The maximum time to wait in milliseconds. If `None`, wait
+                until enough frames are available or the runtime stops.
+            peek:
+                If `True`, frames are left queued when the context exits
+                unless they are consumed with `AvailableData.consume()`.
 
         Returns:
             AvailableDataContext:
                 Context manager for available data for the given VideoStream ID.
+
+        Raises:
+            StateError:
+                On entering the context, if the stream is already being read,
+                e.g. by another context that hasn't exited.
+        """
+        ...
+    def stream_frames(self, stream_id: int) -> FrameStream:
+        """Returns an asynchronous iterator over frames from the given stream.
+
+        Call `stream_frames` with a specific `stream_id`, 0 or 1, and iterate
+        over the result with `async for` to receive frames from the 1st or 2nd
+        video source without blocking the event loop.
+
+        The stream can be created before calling `start()`. It then waits for
+        the runtime to start instead of ending at once.
+
+        Parameters:
+            stream_id:
+                The ID of the stream to read frames from.
+
+        Returns:
+            FrameStream:
+                Asynchronous iterator over the frames of the stream.
+        """
+        ...
+    def set_frame_callback(
+        self,
+        stream_id: int,
+        callback: Optional[Callable[[List[VideoFrame]], None]],
+        batch: int = 1,
+    ) -> None:
+        """Registers a callback that receives frames from the given stream as
+        they are acquired.
+
+        The callback is called from a background thread with a list of up to
+        `batch` frames. The frames are only valid until the callback returns,
+        after which they are released back to the runtime, even if the
+        callback raised. The first exception raised by the callback, or while
+        reading the stream, is raised again by the next call to `stop()`.
+        Until then, the stream's callback isn't called and frames acquired on
+        the stream stay queued. Other streams' callbacks are unaffected.
+        Replacing or removing a callback doesn't discard its exception.
+
+        The callback may itself call `set_frame_callback()` and `stop()`. A
+        callback replacing itself returns before the new one starts reading.
+
+        Parameters:
+            stream_id:
+                The ID of the stream, 0 or 1.
+            callback:
+                Callable that receives a list of `VideoFrame` objects, or
+                `None` to remove the stream's callback.
+            batch:
+                The number of frames to wait for before calling the callback.
+                Fewer frames are passed once the runtime stops.
         """
         ...
+    def get_clock_correlation(self) -> Optional[ClockCorrelation]:
+        """Returns the host clocks sampled when the runtime was last started.
+
+        Use it to convert the `VideoFrameTimestamps` of frames acquired since
+        then to seconds since start, `datetime` or `time.monotonic()` values.
+
+        Returns:
+            Optional[ClockCorrelation]:
+                The clock sample, or `None` if the runtime was never
+                started.
+        """
+        ...
+    def get_stream_stats(self, stream_id: int) -> StreamStats:
+        """Returns acquisition statistics for the given stream.
+
+        Call `get_stream_stats` periodically during a long acquisition to
+        check that frames are consumed as fast as they are produced. The
+        statistics are updated whenever data is read from the stream.
+
+        Parameters:
+            stream_id:
+                The ID of the stream, 0 or 1.
+
+        Returns:
+            StreamStats:
+                The counters for the stream.
+        """
+        ...
+    def get_frame_id_anomalies(self, stream_id: int) -> List[FrameIdAnomaly]:
+        """Returns the frames whose ids did not directly follow the previously
+        read frame's id.
+
+        Frames are checked as they are read from the stream, across
+        successive `get_available_data()` contexts, since the runtime was
+        last started. Only the most recent anomalies are kept; the total
+        number of dropped frames is reported by `get_stream_stats()`.
+
+        Parameters:
+            stream_id:
+                The ID of the stream, 0 or 1.
+
+        Returns:
+            List[FrameIdAnomaly]:
+                The anomalies, oldest first.
+        """
+        ...
+    def set_frame_id_warnings(self, enabled: bool = True) -> None:
+        """Enables or disables logging a warning for each frame id anomaly as
+        frames are read.
+        """
+        ...
+    def get_log_records(
+        self, since: Optional[float] = None
+    ) -> List[LogRecord]:
+        """Returns the messages reported by the core runtime, oldest first.
+
+        Only the most recent messages are kept. Pass the `timestamp` of the
+        last record seen as `since` to get only newer messages.
+
+        Parameters:
+            since:
+                Optional time, in seconds since the unix epoch. Only
+                messages received after this time are returned.
+
+        Returns:
+            List[LogRecord]:
+                The buffered messages.
+        """
+        ...
+    def clear_log_records(self) -> None:
+        """Discards the buffered messages reported by the core runtime."""
+        ...
     def get_configuration(self) -> Properties:
         """Returns the current configuration properties of the runtime.
 
@@ -755,6 +1501,36 @@ class Runtime:
         with this `Runtime` instance.
         """
         ...
+    def validate_configuration(
+        self, properties: Properties
+    ) -> List[ConfigurationViolation]:
+        """Checks `properties` against `get_capabilities()` without applying
+        them.
+
+        Only the currently configured devices can be validated. The runtime
+        only reports the capabilities of devices it has configured, and
+        configuring another one to find out would open it and replace the
+        current configuration. A camera or storage device in `properties`
+        that isn't the configured one is reported as
+        `ViolationKind.DeviceNotConfigured` on its `identifier`, and none of
+        its settings are checked. Apply it with `set_configuration()` first
+        to validate its settings.
+
+        Checks the exposure time and offset against their supported ranges,
+        that offset plus shape fits on the sensor, that the pixel type is
+        supported, that enabled triggers use existing digital lines, and that
+        chunking, sharding and multiscale are only requested from storage
+        that supports them. Ranges a device does not report are not checked.
+
+        Parameters:
+            properties:
+                The configuration to check.
+
+        Returns:
+            List[ConfigurationViolation]:
+                The settings the devices can't honor; empty if none.
+        """
+        ...
     def get_state(self) -> DeviceState:
         """Returns the current state of the device.
 
@@ -762,24 +1538,150 @@ class Runtime:
         this `Runtime` instance.
         """
         ...
-    def set_configuration(self, properties: Properties) -> Properties:
+    def wait_for_state(
+        self, state: DeviceState, timeout_ms: Optional[int] = None
+    ) -> None:
+        """Blocks until the runtime reaches the given state.
+
+        Call `wait_for_state` after `start()` with `DeviceState.Armed` to wait
+        for an acquisition to finish, for example once `max_frame_count`
+        frames have been collected.
+
+        Parameters:
+            state:
+                The `DeviceState` to wait for.
+            timeout_ms:
+                Optional maximum time to wait, in milliseconds. By default,
+                waits indefinitely.
+
+        Raises:
+            AcquireTimeoutError:
+                If the runtime did not reach `state` within `timeout_ms`.
+        """
+        ...
+    def on_state_change(
+        self,
+        callback: Callable[
+            [DeviceState, DeviceState, StateChangeCause], None
+        ],
+    ) -> None:
+        """Registers a callback for changes of the runtime's state.
+
+        The callback is called as `callback(previous, current, cause)`.
+        Changes made by `set_configuration()`, `start()`, `stop()` and
+        `abort()` are reported from within those calls. Changes the runtime
+        makes on its own, like finishing after `max_frame_count` frames, are
+        reported from a background thread that polls the runtime. Exceptions
+        raised by the callback are printed and otherwise ignored.
+
+        Parameters:
+            callback:
+                Callable that receives the previous and the current
+                `DeviceState`, and the `StateChangeCause`.
+        """
+        ...
+    @overload
+    def set_configuration(
+        self,
+        properties: Properties,
+        report: Literal[False] = False,
+        strict: bool = False,
+    ) -> Properties:
         """Applies the provided configuration properties to the runtime.
 
         Call `set_configuration` with a `Properties` object to change the
-        properties of this `Runtime` instance.
+        properties of this `Runtime` instance. Devices may adjust requested
+        values, for example by rounding the exposure time, so the applied
+        configuration can differ from the requested one.
 
         Parameters:
             properties:
                 The properties to be set.
+            report:
+                When `True`, also return the settings that were adjusted.
+                Resolving an identifier's name pattern to a device doesn't
+                count as an adjustment.
+            strict:
+                When `True`, raise if any setting was adjusted.
+
+        Returns:
+            The updated configuration properties. With `report=True`, a
+            tuple of the updated properties and a list of
+            `ConfigurationChange` objects, one for each adjusted setting.
+
+        Raises:
+            ConfigurationError:
+                If the runtime rejects the configuration, or if `strict` is
+                `True` and a setting was adjusted. In the latter case the
+                adjusted configuration has already been applied, and the
+                exception's `changes` attribute lists the adjustments.
+            DeviceNotFoundError:
+                If no loaded device matches the kind and name of a device
+                identifier.
+        """
+        ...
+    @overload
+    def set_configuration(
+        self,
+        properties: Properties,
+        report: Literal[True],
+        strict: bool = False,
+    ) -> Tuple[Properties, List[ConfigurationChange]]: ...
+    @overload
+    def update_configuration(
+        self,
+        patch: Dict[str, Any],
+        report: Literal[False] = False,
+        strict: bool = False,
+    ) -> Properties:
+        """Applies a partial configuration on top of the current one.
+
+        The patch is applied to `get_configuration()` with
+        `Properties.set_path()`, and the result is applied with a single
+        call to the core runtime.
+
+        Example:
+            ```python
+            runtime.update_configuration(
+                {"video[0].camera.settings.exposure_time_us": 5000}
+            )
+            ```
+
+        Parameters:
+            patch:
+                Maps setting paths, like
+                `video[0].camera.settings.exposure_time_us`, to values. A
+                dict value is merged into the settings at its path.
+            report:
+                As for `set_configuration()`.
+            strict:
+                As for `set_configuration()`.
 
         Returns:
-            The updated configuration properties.
+            As for `set_configuration()`.
+
+        Raises:
+            ValueError: If a path doesn't name a setting.
+            TypeError: If a value has the wrong type for its setting.
         """
         ...
+    @overload
+    def update_configuration(
+        self,
+        patch: Dict[str, Any],
+        report: Literal[True],
+        strict: bool = False,
+    ) -> Tuple[Properties, List[ConfigurationChange]]: ...
     def start(self) -> None:
         """Starts the runtime, allowing it to collect data.
 
         Call `start()` to begin data acquisition.
+
+        Raises:
+            StateError:
+                If frames of a stream are still borrowed, e.g. by an array
+                from `VideoFrame.data(copy=False)`, since starting may reuse
+                their memory.
         """
         ...
     def execute_trigger(self, stream_id: int) -> None:
@@ -796,6 +1698,21 @@ class Runtime:
         Call `stop()` to end data acquisition once the max number of frames
         specified in `acquire.VideoStream.max_frame_count` is collected. All
         objects are deleted to free up disk space upon shutdown of `Runtime`.
+
+        Waits up to 10 seconds for callbacks registered with
+        `set_frame_callback()` to receive the remaining frames. Called from a
+        frame callback, it doesn't wait for that callback.
+
+        Raises:
+            Exception:
+                The first exception raised by a frame callback, or while
+                reading frames for one, since the last call to `stop()`, if
+                any, including callbacks since replaced or removed. Every
+                failed callback resumes. When several failed, their
+                exceptions are raised together, in order of stream ID, as an
+                `ExceptionGroup` on Python 3.11 and later. Before that, the
+                first is raised, with the others chained as its
+                `__context__`.
         """
         ...
     def abort(self) -> None:
@@ -994,6 +1911,65 @@ class SignalType:
         """Checks if two SignalType objects are not equal."""
         ...
 
+@final
+class StateChangeCause:
+    """The `StateChangeCause` class indicates what caused a change of the
+    runtime's state.
+
+    Attributes:
+        Configure:
+            Enum-type class variable of `StateChangeCause` that specifies
+            `Runtime.set_configuration()` was called.
+        Start:
+            Enum-type class variable of `StateChangeCause` that specifies
+            `Runtime.start()` was called.
+        Stop:
+            Enum-type class variable of `StateChangeCause` that specifies
+            `Runtime.stop()` was called.
+        Abort:
+            Enum-type class variable of `StateChangeCause` that specifies
+            `Runtime.abort()` was called.
+        Finished:
+            Enum-type class variable of `StateChangeCause` that specifies the
+            runtime changed state on its own, e.g. after acquiring
+            `max_frame_count` frames, or after a device failed.
+    """
+
+    Configure: ClassVar[StateChangeCause]
+    Start: ClassVar[StateChangeCause]
+    Stop: ClassVar[StateChangeCause]
+    Abort: ClassVar[StateChangeCause]
+    Finished: ClassVar[StateChangeCause]
+
+    def __eq__(self, other: object) -> bool:
+        """Checks if two StateChangeCause objects are equal."""
+        ...
+    def __ge__(self, other: object) -> bool:
+        """Checks if this StateChangeCause is greater than or equal to
+        another."""
+        ...
+    def __gt__(self, other: object) -> bool:
+        """Checks if this StateChangeCause is greater than another."""
+        ...
+    def __int__(self) -> int:
+        """Converts the StateChangeCause to an integer."""
+        ...
+    def __le__(self, other: object) -> bool:
+        """Checks if this StateChangeCause is less than or equal to
+        another."""
+        ...
+    def __lt__(self, other: object) -> bool:
+        """Checks if this StateChangeCause is less than another."""
+        ...
+    def __ne__(self, other: object) -> bool:
+        """Checks if two StateChangeCause objects are not equal."""
+        ...
+
+class StateError(AcquireError):
+    """Raised when the runtime is not in a state that allows the request, for
+    example when calling `start()` before `set_configuration()`.
+    """
+
 @final
 class Storage:
     """The `Storage` class represents storage devices and their settings.
@@ -1073,6 +2049,9 @@ class StorageDimension:
         """Returns a dictionary of the `StorageDimensions` object's attributes."""
         ...
 
+class StorageError(AcquireError):
+    """Raised when storage properties could not be set up."""
+
 @final
 class StorageProperties:
     """The `StorageProperties` class represents properties for data storage.
@@ -1115,6 +2094,51 @@ class StorageProperties:
         """Returns a dictionary of the `StorageProperties` object's attributes."""
         ...
 
+@final
+class StreamStats:
+    """The `StreamStats` class represents counters describing how data flows
+    through a video stream since the runtime was last started.
+
+    Attributes:
+        frames_observed:
+            The number of frames seen in the stream's queue: those consumed
+            plus those still queued. This is a lower bound on the number of
+            frames the runtime wrote, since frames dropped before the queue
+            was mapped aren't seen.
+        frames_consumed:
+            The number of frames read from the queue and released back to the
+            runtime.
+        frames_produced:
+            Alias of `frames_observed`.
+        bytes_consumed:
+            The number of bytes of frame data released back to the runtime.
+        bytes_mapped:
+            Alias of `bytes_consumed`.
+        queued_frames:
+            The number of frames waiting in the queue.
+        max_queued_frames:
+            The largest number of frames observed in the queue.
+        frames_dropped:
+            The number of frames missing from gaps in the frame ids.
+        frame_rate_hz:
+            The frame rate over the most recently consumed frames, computed
+            from their `VideoFrameTimestamps.acq_thread` timestamps.
+    """
+
+    frames_observed: int
+    frames_produced: int
+    frames_consumed: int
+    bytes_consumed: int
+    bytes_mapped: int
+    queued_frames: int
+    max_queued_frames: int
+    frames_dropped: int
+    frame_rate_hz: float
+
+    def dict(self) -> Dict[str, Any]:
+        """Returns a dictionary of a `StreamStats` object's attributes."""
+        ...
+
 @final
 class Trigger:
     """The `Trigger` class represents a trigger signal.
@@ -1267,10 +2291,90 @@ class TriggerInputOutputCapabilities:
 class VideoFrame:
     """The `VideoFrame` class represents data from acquisition of a frame."""
 
-    def data(self) -> NDArray[Any]:
+    def data(self, copy: bool = True, raw: bool = False) -> NDArray[Any]:
         """Returns the data of the video frame as an NDArray.
 
-        Call `data()` to create an NDArray of the `VideoFrame` data.
+        Call `data()` to create an NDArray of the `VideoFrame` data. Padded
+        rows or planes, as described by `shape_info().strides`, are skipped.
+
+        Samples of type `SampleType.U10`, `SampleType.U12` and
+        `SampleType.U14` are returned as `uint16`. If the driver packs them
+        into fewer bits, they are unpacked into a new array, so `copy` must
+        be `True`.
+
+        Parameters:
+            copy:
+                When `False`, returns a read-only array backed directly by
+                the frame's memory instead of a copy. The array's `base` is a
+                `FrameDataGuard`, which keeps the frame in the video queue
+                while the array is alive. The stream can still be read, but
+                frames aren't released to the runtime until the array is
+                deleted, so copy the array to keep the data without filling
+                up the queue.
+            raw:
+                When `True`, returns the image bytes as delivered by the
+                driver, as a 1-d `uint8` array, without unpacking them.
+
+        Raises:
+            RuntimeError:
+                If called after the `AvailableDataContext` has exited, or if
+                the frame's sample type is not recognized.
+            ValueError:
+                If `copy` is `False` and the samples are packed.
+        """
+        ...
+    def __dlpack__(
+        self,
+        *,
+        stream: Optional[Any] = None,
+        max_version: Optional[Tuple[int, int]] = None,
+        dl_device: Optional[Tuple[int, int]] = None,
+        copy: Optional[bool] = None,
+    ) -> Any:
+        """Exports the frame's image data as a DLPack capsule.
+
+        Lets `numpy.from_dlpack()`, `torch.from_dlpack()` and other DLPack
+        consumers use the frame. The tensor has the same
+        `(planes, height, width, channels)` shape and dtype as `data()`.
+
+        Only consumers that pass `max_version=(1, 0)` or later get a
+        read-only tensor over the frame's memory, which stays mapped until
+        the consumer releases it. Consumers that don't pass `max_version`,
+        which includes many torch and JAX releases, can't be told the memory
+        is read-only, so they get a copy of every frame. `copy=True` always
+        exports a copy.
+
+        For a view without DLPack 1.0, use `frame.data(copy=False)`, e.g.
+        `memoryview(frame.data(copy=False))` for the buffer protocol.
+
+        Raises:
+            RuntimeError:
+                If called after the `AvailableDataContext` has exited.
+            BufferError:
+                If the samples are packed, if a device other than the CPU is
+                requested, or if `copy=False` is requested without
+                `max_version=(1, 0)` or later.
+        """
+        ...
+    def __dlpack_device__(self) -> Tuple[int, int]:
+        """Returns the DLPack device of the frame's memory, which is always
+        the CPU.
+        """
+        ...
+    def shape_info(self) -> FrameShapeInfo:
+        """Returns how the frame's image data is laid out in memory.
+
+        Raises:
+            RuntimeError:
+                If called after the `AvailableDataContext` has exited.
+        """
+        ...
+    def detach(self) -> OwnedVideoFrame:
+        """Copies the frame so it can be used after the context exits.
+
+        Raises:
+            RuntimeError:
+                If called after the `AvailableDataContext` has exited.
         """
         ...
     def metadata(self) -> VideoFrameMetadata:
@@ -1293,10 +2397,26 @@ class VideoFrameMetadata:
             An instance of the `VideoFrameTimestamps` class specifying the
             video timestamps based on the hardware clock and the acquisition
             clock.
+        stream_id:
+            The ID of the video stream the frame was read from.
+        dims:
+            The `(planes, height, width, channels)` shape of the frame.
+        strides:
+            The strides of each dimension in `dims`, in samples, as reported
+            by the driver.
+        sample_type:
+            The `SampleType` of the frame.
+        bytes_of_frame:
+            The size of the frame in bytes, including its header.
     """
 
     frame_id: int
     timestamps: VideoFrameTimestamps
+    stream_id: int
+    dims: Tuple[int, int, int, int]
+    strides: Tuple[int, int, int, int]
+    sample_type: SampleType
+    bytes_of_frame: int
 
     def dict(self) -> Dict[str, Any]:
         """Returns a dictionary of the `VideoFrameMetadata` object's attributes."""
@@ -1378,6 +2498,58 @@ class VideoStreamCapabilities:
         """
         ...
 
+@final
+class ViolationKind:
+    """The `ViolationKind` class represents what is wrong with a setting
+    reported by `Runtime.validate_configuration()`.
+
+    Attributes:
+        OutOfRange:
+            Enum-type class variable of `ViolationKind` that specifies a
+            value is outside the range the device supports.
+        UnsupportedPixelType:
+            Enum-type class variable of `ViolationKind` that specifies the
+            camera does not support the pixel type.
+        InvalidTriggerLine:
+            Enum-type class variable of `ViolationKind` that specifies a
+            trigger uses a line the camera does not have.
+        UnsupportedStorageFeature:
+            Enum-type class variable of `ViolationKind` that specifies the
+            storage device does not support a requested feature.
+        DeviceNotConfigured:
+            Enum-type class variable of `ViolationKind` that specifies the
+            device is not the one currently configured, so its settings
+            were not checked.
+    """
+
+    OutOfRange: ClassVar[ViolationKind]
+    UnsupportedPixelType: ClassVar[ViolationKind]
+    InvalidTriggerLine: ClassVar[ViolationKind]
+    UnsupportedStorageFeature: ClassVar[ViolationKind]
+    DeviceNotConfigured: ClassVar[ViolationKind]
+
+    def __eq__(self, other: object) -> bool:
+        """Checks if two ViolationKind objects are equal."""
+        ...
+    def __ge__(self, other: object) -> bool:
+        """Checks if this ViolationKind is greater than or equal to another."""
+        ...
+    def __gt__(self, other: object) -> bool:
+        """Checks if this ViolationKind is greater than another."""
+        ...
+    def __int__(self) -> int:
+        """Converts the ViolationKind to an integer."""
+        ...
+    def __le__(self, other: object) -> bool:
+        """Checks if this ViolationKind is less than or equal to another."""
+        ...
+    def __lt__(self, other: object) -> bool:
+        """Checks if this ViolationKind is less than another."""
+        ...
+    def __ne__(self, other: object) -> bool:
+        """Checks if two ViolationKind objects are not equal."""
+        ...
+
 @final
 class VoltageRange:
     """The `VoltageRange` class represents a range of voltage values.
@@ -1404,6 +2576,49 @@ class VoltageRange:
         """Returns a dictionary of the `VoltageRange` object's attributes."""
         ...
 
+@final
+class WaitStatus:
+    """The `WaitStatus` class indicates why `runtime.get_available_data()`
+    stopped waiting for frames.
+
+    Attributes:
+        Ready:
+            Enum-type class variable of `WaitStatus` that specifies at least
+            the requested number of frames were available.
+        TimedOut:
+            Enum-type class variable of `WaitStatus` that specifies the timeout
+            expired before enough frames were available.
+        Stopped:
+            Enum-type class variable of `WaitStatus` that specifies the runtime
+            left the `Running` state before enough frames were available.
+    """
+
+    Ready: ClassVar[WaitStatus]
+    TimedOut: ClassVar[WaitStatus]
+    Stopped: ClassVar[WaitStatus]
+
+    def __eq__(self, other: object) -> bool:
+        """Checks if two WaitStatus objects are equal."""
+        ...
+    def __ge__(self, other: object) -> bool:
+        """Checks if this WaitStatus is greater than or equal to another."""
+        ...
+    def __gt__(self, other: object) -> bool:
+        """Checks if this WaitStatus is greater than another."""
+        ...
+    def __int__(self) -> int:
+        """Converts the WaitStatus to an integer."""
+        ...
+    def __le__(self, other: object) -> bool:
+        """Checks if this WaitStatus is less than or equal to another."""
+        ...
+    def __lt__(self, other: object) -> bool:
+        """Checks if this WaitStatus is less than another."""
+        ...
+    def __ne__(self, other: object) -> bool:
+        """Checks if two WaitStatus objects are not equal."""
+        ...
+
 def core_api_version() -> str:
     """Returns the version string for the core API."""
     ...
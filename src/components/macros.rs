@@ -34,8 +34,13 @@ pub(crate) use cvt;
 // FIXME: (nclack) modularize the parts, dedup code
 macro_rules! impl_plain_old_dict {
     (@out $T:ty) => {
+        crate::components::macros::impl_plain_old_dict!(@out $T, {});
+    };
+    (@out $T:ty, { $($methods:tt)* }) => {
         #[pymethods]
         impl $T {
+            $($methods)*
+
             #[doc=concat!("Make a dict representation of ",stringify!($T))]
             fn dict(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
                 Ok(pythonize::pythonize(py, self)?)
@@ -55,13 +60,18 @@ macro_rules! impl_plain_old_dict {
         }
     };
     ($T:ty) => {
+        crate::components::macros::impl_plain_old_dict!($T, {});
+    };
+    ($T:ty, { $($methods:tt)* }) => {
         #[pymethods]
         impl $T {
+            $($methods)*
+
             #[new]
-            #[pyo3(signature = (**kwargs))]
-            fn __new__(kwargs: Option<&pyo3::types::PyDict>) -> anyhow::Result<Self> {
+            #[pyo3(signature = (*, strict=true, **kwargs))]
+            fn __new__(strict: bool, kwargs: Option<&pyo3::types::PyDict>) -> PyResult<Self> {
                 if let Some(kwargs) = kwargs {
-                    Ok(pythonize::depythonize(kwargs)?)
+                    crate::strict::depythonize(kwargs, strict)
                 } else {
                     Ok(Default::default())
                 }
@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 use std::slice::Iter;
 
 #[pyclass]
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SampleType {
     U8,
     U16,
@@ -38,6 +38,32 @@ impl SampleType {
         ];
         SAMPLE_TYPES.iter()
     }
+
+    /// The number of significant bits in each sample.
+    pub(crate) fn bits_per_sample(&self) -> usize {
+        match self {
+            SampleType::U8 | SampleType::I8 => 8,
+            SampleType::U10 => 10,
+            SampleType::U12 => 12,
+            SampleType::U14 => 14,
+            SampleType::U16 | SampleType::I16 => 16,
+            SampleType::F32 => 32,
+        }
+    }
+
+    /// The size of each sample when it is not packed. Samples with fewer than
+    /// 16 significant bits are stored in 16 bits.
+    pub(crate) fn bytes_per_sample(&self) -> usize {
+        match self {
+            SampleType::U8 | SampleType::I8 => 1,
+            SampleType::U10
+            | SampleType::U12
+            | SampleType::U14
+            | SampleType::U16
+            | SampleType::I16 => 2,
+            SampleType::F32 => 4,
+        }
+    }
 }
 
 cvt!(SampleType => capi::SampleType,
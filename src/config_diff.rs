@@ -0,0 +1,150 @@
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList, PyTuple};
+
+use crate::core_properties::Properties;
+
+/// A setting that was applied with a different value than was requested.
+#[pyclass]
+#[derive(Debug, Clone)]
+pub(crate) struct ConfigurationChange {
+    /// Path to the setting from the `Properties`, e.g.
+    /// `video[0].camera.settings.exposure_time_us`.
+    #[pyo3(get)]
+    field: String,
+
+    #[pyo3(get)]
+    requested: PyObject,
+
+    #[pyo3(get)]
+    applied: PyObject,
+}
+
+#[pymethods]
+impl ConfigurationChange {
+    #[doc = "Make a dict representation of ConfigurationChange"]
+    fn dict<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        let dict = PyDict::new(py);
+        dict.set_item("field", &self.field)?;
+        dict.set_item("requested", &self.requested)?;
+        dict.set_item("applied", &self.applied)?;
+        Ok(dict)
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(format!(
+            "ConfigurationChange(field={:?},requested={},applied={})",
+            self.field,
+            self.requested.as_ref(py).repr()?,
+            self.applied.as_ref(py).repr()?
+        ))
+    }
+}
+
+impl ConfigurationChange {
+    /// Describes the change on one line.
+    pub(crate) fn describe(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(format!(
+            "{}: requested {}, applied {}",
+            self.field,
+            self.requested.as_ref(py).repr()?,
+            self.applied.as_ref(py).repr()?
+        ))
+    }
+}
+
+/// Returns a change for every setting whose applied value differs from the
+/// requested one.
+pub(crate) fn diff(
+    py: Python<'_>,
+    requested: &Properties,
+    applied: &Properties,
+) -> PyResult<Vec<ConfigurationChange>> {
+    let requested = pythonize::pythonize(py, requested)?;
+    let applied = pythonize::pythonize(py, applied)?;
+    let mut changes = Vec::new();
+    walk(
+        py,
+        String::new(),
+        Some(requested.as_ref(py)),
+        Some(applied.as_ref(py)),
+        &mut changes,
+    )?;
+    Ok(changes)
+}
+
+/// Compares two values from `dict()` representations, recursing into dicts
+/// and sequences. `None` stands for a key or item missing from one side.
+fn walk(
+    py: Python<'_>,
+    path: String,
+    requested: Option<&PyAny>,
+    applied: Option<&PyAny>,
+    changes: &mut Vec<ConfigurationChange>,
+) -> PyResult<()> {
+    let join = |key: &str| {
+        if path.is_empty() {
+            key.to_owned()
+        } else {
+            format!("{}.{}", path, key)
+        }
+    };
+
+    if let (Some(r), Some(a)) = (requested, applied) {
+        if let (Ok(r), Ok(a)) = (r.downcast::<PyDict>(), a.downcast::<PyDict>()) {
+            for (key, value) in r {
+                walk(
+                    py,
+                    join(&key.str()?.to_string_lossy()),
+                    Some(value),
+                    a.get_item(key)?,
+                    changes,
+                )?;
+            }
+            for (key, value) in a {
+                if !r.contains(key)? {
+                    walk(
+                        py,
+                        join(&key.str()?.to_string_lossy()),
+                        None,
+                        Some(value),
+                        changes,
+                    )?;
+                }
+            }
+            return Ok(());
+        }
+        if let (Some(r), Some(a)) = (as_items(r), as_items(a)) {
+            for i in 0..r.len().max(a.len()) {
+                walk(
+                    py,
+                    format!("{}[{}]", path, i),
+                    r.get(i).copied(),
+                    a.get(i).copied(),
+                    changes,
+                )?;
+            }
+            return Ok(());
+        }
+        if r.eq(a)? {
+            return Ok(());
+        }
+    }
+
+    changes.push(ConfigurationChange {
+        field: path,
+        requested: requested.map_or_else(|| py.None(), |r| r.into()),
+        applied: applied.map_or_else(|| py.None(), |a| a.into()),
+    });
+    Ok(())
+}
+
+/// The items of a list or tuple.
+fn as_items(value: &PyAny) -> Option<Vec<&PyAny>> {
+    if let Ok(list) = value.downcast::<PyList>() {
+        Some(list.iter().collect())
+    } else if let Ok(tuple) = value.downcast::<PyTuple>() {
+        Some(tuple.iter().collect())
+    } else {
+        None
+    }
+}
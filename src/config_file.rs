@@ -0,0 +1,229 @@
+use anyhow::{anyhow, Result};
+use serde_json::{Map, Value};
+use std::path::Path;
+
+use crate::{core_properties::Properties, strict};
+
+/// The version of the layout written by `Properties.to_json()` and friends.
+///
+/// Bump it whenever a change to `Properties` would stop older files from
+/// loading, and add a step to `MIGRATIONS` that upgrades them.
+pub(crate) const SCHEMA_VERSION: u64 = 2;
+
+/// The key holding the schema version in saved files.
+const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// Steps that upgrade a document from the version at their index to the
+/// next one.
+const MIGRATIONS: [fn(&mut Map<String, Value>); SCHEMA_VERSION as usize] = [
+    // 0 -> 1: files saved from `Properties.dict()` before they were
+    // versioned. The layout is unchanged.
+    |_| {},
+    // 1 -> 2: device identifiers no longer carry their ids.
+    |document| {
+        let streams = document.get_mut("video").and_then(Value::as_array_mut);
+        for stream in streams.into_iter().flatten() {
+            for device in ["camera", "storage"] {
+                let identifier = stream
+                    .pointer_mut(&format!("/{}/identifier", device))
+                    .and_then(Value::as_object_mut);
+                if let Some(identifier) = identifier {
+                    identifier.remove("id");
+                }
+            }
+        }
+    },
+];
+
+/// The text formats `Properties` can be saved in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Format {
+    Json,
+    Toml,
+}
+
+impl Format {
+    /// TOML for paths ending in `.toml`, JSON otherwise.
+    pub(crate) fn of(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(e) if e.eq_ignore_ascii_case("toml") => Format::Toml,
+            _ => Format::Json,
+        }
+    }
+}
+
+/// Serializes `properties` along with the current schema version.
+pub(crate) fn to_string(properties: &Properties, format: Format) -> Result<String> {
+    let mut document = match serde_json::to_value(properties)? {
+        Value::Object(document) => document,
+        _ => return Err(anyhow!("Properties did not serialize to a table")),
+    };
+    document.insert(SCHEMA_VERSION_KEY.into(), SCHEMA_VERSION.into());
+    let document = Value::Object(document);
+    Ok(match format {
+        Format::Json => serde_json::to_string_pretty(&document)?,
+        // TOML has no null, so unset optional settings are left out.
+        Format::Toml => {
+            let document = without_nulls(document);
+            check_toml_integers(&document, &mut String::new())?;
+            toml::to_string_pretty(&document)?
+        }
+    })
+}
+
+/// Parses `text`, upgrading documents written with older schema versions.
+///
+/// Unknown keys are errors when `strict` is set. See `strict::deserialize()`.
+pub(crate) fn from_str(text: &str, format: Format, strict: bool) -> Result<Properties> {
+    let document: Value = match format {
+        Format::Json => serde_json::from_str(text)?,
+        Format::Toml => toml::from_str(text)?,
+    };
+    let mut document = match document {
+        Value::Object(document) => document,
+        _ => return Err(anyhow!("Expected a table of properties")),
+    };
+
+    upgrade(&mut document)?;
+    strict::deserialize(Value::Object(document), strict)
+}
+
+/// Upgrades `document` from the schema version it was written with to the
+/// current one, removing the version key.
+fn upgrade(document: &mut Map<String, Value>) -> Result<()> {
+    let version = match document.remove(SCHEMA_VERSION_KEY) {
+        None => 0,
+        Some(v) => v
+            .as_u64()
+            .ok_or_else(|| anyhow!("Invalid {}: {}", SCHEMA_VERSION_KEY, v))?,
+    };
+    if version > SCHEMA_VERSION {
+        return Err(anyhow!(
+            "Properties have schema version {}, but only versions up to {} are supported. \
+             Upgrade acquire to load them.",
+            version,
+            SCHEMA_VERSION
+        ));
+    }
+    for migrate in &MIGRATIONS[version as usize..] {
+        migrate(document);
+    }
+    Ok(())
+}
+
+/// Fails on the first integer in `value` that TOML can't hold, naming the
+/// setting at `path`. TOML integers are signed 64 bit.
+fn check_toml_integers(value: &Value, path: &mut String) -> Result<()> {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                let len = path.len();
+                strict::push_key(path, key);
+                check_toml_integers(value, path)?;
+                path.truncate(len);
+            }
+        }
+        Value::Array(items) => {
+            for (index, value) in items.iter().enumerate() {
+                let len = path.len();
+                strict::push_index(path, index);
+                check_toml_integers(value, path)?;
+                path.truncate(len);
+            }
+        }
+        Value::Number(n) if n.is_u64() && n.as_i64().is_none() => {
+            return Err(anyhow!(
+                "Can't save {} = {} as TOML, which only holds integers up to {}. \
+                 Save as JSON instead.",
+                path,
+                n,
+                i64::MAX
+            ));
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn without_nulls(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .filter(|(_, v)| !v.is_null())
+                .map(|(k, v)| (k, without_nulls(v)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(without_nulls).collect()),
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn upgraded(document: Value) -> Value {
+        let mut document = match document {
+            Value::Object(document) => document,
+            _ => unreachable!(),
+        };
+        upgrade(&mut document).unwrap();
+        Value::Object(document)
+    }
+
+    fn stream(id: Option<Value>) -> Value {
+        let mut identifier = json!({"kind": "Camera", "name": "simulated: empty"});
+        if let Some(id) = id {
+            identifier["id"] = id;
+        }
+        json!({
+            "camera": {"identifier": identifier.clone(), "settings": {}},
+            "storage": {"identifier": identifier, "settings": {}},
+        })
+    }
+
+    #[test]
+    fn strips_identifier_ids_from_version_1() {
+        let document = json!({
+            "schema_version": 1,
+            "video": [stream(Some(json!([1, 2]))), stream(Some(json!([0, 0])))],
+        });
+        assert_eq!(
+            upgraded(document),
+            json!({"video": [stream(None), stream(None)]})
+        );
+    }
+
+    #[test]
+    fn strips_identifier_ids_from_unversioned_documents() {
+        let document = json!({"video": [stream(Some(json!([1, 2])))]});
+        assert_eq!(upgraded(document), json!({"video": [stream(None)]}));
+    }
+
+    #[test]
+    fn leaves_current_documents_alone() {
+        let document = json!({
+            "schema_version": SCHEMA_VERSION,
+            "video": [stream(None)],
+        });
+        assert_eq!(upgraded(document), json!({"video": [stream(None)]}));
+    }
+
+    #[test]
+    fn rejects_integers_too_large_for_toml() {
+        let document = json!({"video": [{"max_frame_count": 1}, {"max_frame_count": u64::MAX}]});
+        let err = check_toml_integers(&document, &mut String::new()).unwrap_err();
+        assert!(err.to_string().contains("video[1].max_frame_count"));
+
+        let document = json!({"video": [{"max_frame_count": i64::MAX}]});
+        assert!(check_toml_integers(&document, &mut String::new()).is_ok());
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let mut document = Map::new();
+        document.insert(SCHEMA_VERSION_KEY.into(), (SCHEMA_VERSION + 1).into());
+        assert!(upgrade(&mut document).is_err());
+    }
+}
@@ -0,0 +1,119 @@
+use pyo3::{
+    exceptions::{PyAttributeError, PyTypeError, PyValueError},
+    prelude::*,
+    types::{PyDict, PyList, PyTuple},
+};
+
+/// A step in a path like `video[0].camera.settings.shape[1]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+fn parse(path: &str) -> PyResult<Vec<Segment>> {
+    let invalid = || PyValueError::new_err(format!("Invalid setting path: {:?}", path));
+    let mut segments = Vec::new();
+    for part in path.split('.') {
+        let (key, mut rest) = part.split_at(part.find('[').unwrap_or(part.len()));
+        if key.is_empty() {
+            return Err(invalid());
+        }
+        segments.push(Segment::Key(key.to_owned()));
+        while !rest.is_empty() {
+            let end = rest.find(']').ok_or_else(invalid)?;
+            let index = rest[1..end].parse().map_err(|_| invalid())?;
+            segments.push(Segment::Index(index));
+            rest = &rest[end + 1..];
+            if !rest.is_empty() && !rest.starts_with('[') {
+                return Err(invalid());
+            }
+        }
+    }
+    Ok(segments)
+}
+
+fn get_one<'py>(obj: &'py PyAny, segment: &Segment) -> PyResult<&'py PyAny> {
+    match segment {
+        Segment::Key(key) => match obj.downcast::<PyDict>() {
+            Ok(dict) => dict
+                .get_item(key)?
+                .ok_or_else(|| PyAttributeError::new_err(key.clone())),
+            Err(_) => obj.getattr(key.as_str()),
+        },
+        Segment::Index(index) => obj.get_item(*index),
+    }
+}
+
+fn set_one(obj: &PyAny, segment: &Segment, value: &PyAny) -> PyResult<()> {
+    match segment {
+        Segment::Key(key) => match obj.downcast::<PyDict>() {
+            Ok(dict) => dict.set_item(key, value),
+            Err(_) => {
+                // Only replace existing settings, rather than failing later
+                // on an attribute the setter doesn't know.
+                obj.getattr(key.as_str())?;
+                obj.setattr(key.as_str(), value)
+            }
+        },
+        Segment::Index(index) => obj.set_item(*index, value),
+    }
+}
+
+fn set_in(obj: &PyAny, path: &[Segment], value: &PyAny) -> PyResult<()> {
+    match path {
+        [] => Err(PyValueError::new_err("Empty setting path")),
+        [segment] => set_one(obj, segment, value),
+        [segment, rest @ ..] => {
+            // Getters of settings that aren't held as Python objects, like
+            // the triggers in `input_triggers`, return copies, so the child is
+            // always stored back once it's modified.
+            let child = get_one(obj, segment)?;
+            if let Ok(tuple) = child.downcast::<PyTuple>() {
+                // Tuples like `shape` can't be modified in place.
+                let items = PyList::new(obj.py(), tuple);
+                set_in(items, rest, value)?;
+                set_one(obj, segment, items.to_tuple())
+            } else {
+                set_in(child, rest, value)?;
+                set_one(obj, segment, child)
+            }
+        }
+    }
+}
+
+/// Names the setting at `path` in errors raised while accessing it.
+fn in_context(py: Python<'_>, path: &str, err: PyErr) -> PyErr {
+    if err.is_instance_of::<PyAttributeError>(py) {
+        PyValueError::new_err(format!("Unknown setting: {}", path))
+    } else if err.is_instance_of::<PyTypeError>(py) {
+        PyTypeError::new_err(format!("Invalid value for {}: {}", path, err.value(py)))
+    } else {
+        err
+    }
+}
+
+/// Returns the value at `path` under `root`.
+pub(crate) fn get<'py>(root: &'py PyAny, path: &str) -> PyResult<&'py PyAny> {
+    parse(path)?
+        .iter()
+        .try_fold(root, get_one)
+        .map_err(|e| in_context(root.py(), path, e))
+}
+
+/// Sets the value at `path` under `root`.
+///
+/// A dict `value` is merged into a setting that isn't itself a dict, so
+/// `{"exposure_time_us": 5000}` applied at `video[0].camera.settings` leaves
+/// the other camera settings alone.
+pub(crate) fn set(root: &PyAny, path: &str, value: &PyAny) -> PyResult<()> {
+    if let Ok(patch) = value.downcast::<PyDict>() {
+        if !get(root, path)?.is_instance_of::<PyDict>() {
+            for (key, value) in patch {
+                set(root, &format!("{}.{}", path, key.str()?), value)?;
+            }
+            return Ok(());
+        }
+    }
+    set_in(root, &parse(path)?, value).map_err(|e| in_context(root.py(), path, e))
+}
@@ -1,19 +1,28 @@
-use pyo3::prelude::*;
+use pyo3::{exceptions::PyValueError, prelude::*};
 use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, ptr::NonNull};
 
 use crate::{
-    camera::CameraProperties, capi, components::macros::impl_plain_old_dict,
-    device::DeviceIdentifier, storage::StorageProperties,
+    camera::CameraProperties,
+    capi,
+    components::macros::impl_plain_old_dict,
+    config_file::{self, Format},
+    config_path,
+    device::DeviceIdentifier,
+    device_manager,
+    runtime::Runtime,
+    storage::StorageProperties,
+    strict,
 };
 
 #[pyclass]
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Camera {
     #[pyo3(get, set)]
-    identifier: Option<Py<DeviceIdentifier>>,
+    pub(crate) identifier: Option<Py<DeviceIdentifier>>,
 
     #[pyo3(get, set)]
-    settings: Py<CameraProperties>,
+    pub(crate) settings: Py<CameraProperties>,
 }
 
 impl Default for Camera {
@@ -72,10 +81,10 @@ impl TryFrom<&Camera> for capi::AcquireProperties_aq_properties_video_s_aq_prope
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Storage {
     #[pyo3(get, set)]
-    identifier: Option<Py<DeviceIdentifier>>,
+    pub(crate) identifier: Option<Py<DeviceIdentifier>>,
 
     #[pyo3(get, set)]
-    settings: Py<StorageProperties>,
+    pub(crate) settings: Py<StorageProperties>,
 
     write_delay_ms: f32,
 }
@@ -135,10 +144,10 @@ impl TryFrom<&Storage> for capi::AcquireProperties_aq_properties_video_s_aq_prop
 #[derive(Clone, Serialize, Deserialize)]
 pub struct VideoStream {
     #[pyo3(get, set)]
-    camera: Py<Camera>,
+    pub(crate) camera: Py<Camera>,
 
     #[pyo3(get, set)]
-    storage: Py<Storage>,
+    pub(crate) storage: Py<Storage>,
 
     #[pyo3(get, set)]
     max_frame_count: u64,
@@ -202,7 +211,7 @@ impl TryFrom<&VideoStream> for capi::AcquireProperties_aq_properties_video_s {
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Properties {
     #[pyo3(get, set)]
-    video: (Py<VideoStream>, Py<VideoStream>), // TODO: should be List of VideoStream? Are there ownership/reference problems?
+    pub(crate) video: (Py<VideoStream>, Py<VideoStream>), // TODO: should be List of VideoStream? Are there ownership/reference problems?
 }
 
 impl Default for Properties {
@@ -219,10 +228,10 @@ impl Default for Properties {
 #[pymethods]
 impl Properties {
     #[new]
-    #[pyo3(signature = (**kwargs))]
-    fn __new__(kwargs: Option<&pyo3::types::PyDict>) -> anyhow::Result<Self> {
+    #[pyo3(signature = (*, strict=true, **kwargs))]
+    fn __new__(strict: bool, kwargs: Option<&pyo3::types::PyDict>) -> PyResult<Self> {
         if let Some(kwargs) = kwargs {
-            Ok(pythonize::depythonize(kwargs)?)
+            strict::depythonize(kwargs, strict)
         } else {
             Ok(Default::default())
         }
@@ -243,6 +252,101 @@ impl Properties {
 
         Ok(format!("Properties({})", args))
     }
+
+    /// Returns the setting at a path like `video[0].camera.settings.shape`.
+    fn get_path(slf: &PyCell<Self>, path: &str) -> PyResult<PyObject> {
+        Ok(config_path::get(slf, path)?.into())
+    }
+
+    /// Sets the setting at a path like `video[0].camera.settings.shape[0]`.
+    ///
+    /// A dict `value` is merged into the settings at `path`.
+    fn set_path(slf: &PyCell<Self>, path: &str, value: &PyAny) -> PyResult<()> {
+        config_path::set(slf, path, value)
+    }
+
+    /// Serializes the properties to JSON, tagged with the schema version.
+    fn to_json(&self) -> PyResult<String> {
+        config_file::to_string(self, Format::Json).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Parses properties from JSON written by `to_json()`, or from a
+    /// serialized `dict()`.
+    ///
+    /// Unknown keys are errors unless `strict` is false.
+    #[staticmethod]
+    #[pyo3(signature = (text, strict=true))]
+    fn from_json(text: &str, strict: bool) -> PyResult<Self> {
+        config_file::from_str(text, Format::Json, strict)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Serializes the properties to TOML, tagged with the schema version.
+    fn to_toml(&self) -> PyResult<String> {
+        config_file::to_string(self, Format::Toml).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (text, strict=true))]
+    fn from_toml(text: &str, strict: bool) -> PyResult<Self> {
+        config_file::from_str(text, Format::Toml, strict)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Writes the properties to `path`, as TOML if it ends in `.toml` and as
+    /// JSON otherwise.
+    fn save(&self, path: PathBuf) -> PyResult<()> {
+        let text = config_file::to_string(self, Format::of(&path))
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(std::fs::write(path, text)?)
+    }
+
+    /// Reads properties saved by `save()`.
+    ///
+    /// When `runtime` is given, the devices are looked up in it so that
+    /// missing ones are reported here rather than by `set_configuration()`.
+    #[staticmethod]
+    #[pyo3(signature = (path, runtime=None, strict=true))]
+    fn load(
+        path: PathBuf,
+        runtime: Option<PyRef<Runtime>>,
+        strict: bool,
+        py: Python<'_>,
+    ) -> PyResult<Self> {
+        let text = std::fs::read_to_string(&path)?;
+        let format = Format::of(&path);
+        let properties: Self = config_file::from_str(&text, format, strict).map_err(|e| {
+            PyValueError::new_err(format!("Could not load {}: {}", path.display(), e))
+        })?;
+        if let Some(runtime) = runtime {
+            properties.resolve_devices(py, runtime.device_manager()?.inner)?;
+        }
+        Ok(properties)
+    }
+}
+
+impl Properties {
+    /// Looks up the device named by each identifier, filling in the ids used
+    /// by the current runtime. See `device_manager::resolve()`.
+    fn resolve_devices(&self, py: Python<'_>, dm: NonNull<capi::DeviceManager>) -> PyResult<()> {
+        for (i, stream) in [&self.video.0, &self.video.1].into_iter().enumerate() {
+            let stream = stream.borrow(py);
+            let camera = stream.camera.borrow(py);
+            let storage = stream.storage.borrow(py);
+            for (name, identifier) in [
+                ("camera", &camera.identifier),
+                ("storage", &storage.identifier),
+            ] {
+                if let Some(identifier) = identifier {
+                    let mut raw: capi::DeviceIdentifier = (&*identifier.borrow(py)).try_into()?;
+                    let field = format!("video[{}].{}.identifier", i, name);
+                    unsafe { device_manager::resolve(dm, &mut raw, &field) }?;
+                    *identifier.borrow_mut(py) = raw.try_into()?;
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 impl TryFrom<&capi::AcquireProperties> for Properties {
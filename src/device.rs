@@ -25,7 +25,7 @@ impl Default for capi::DeviceIdentifier {
 }
 
 #[pyclass]
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DeviceState {
     Closed,
     AwaitingConfiguration,
@@ -90,7 +90,11 @@ impl TryFrom<&str> for DeviceKind {
 #[pyclass]
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 pub(crate) struct DeviceIdentifier {
+    /// The driver and device ids. These depend on the order drivers were
+    /// loaded, so they aren't serialized; devices are looked up by `kind`
+    /// and `name` when a configuration is applied.
     #[pyo3(get)]
+    #[serde(skip)]
     id: (u8, u8),
 
     #[pyo3(get)]
@@ -100,12 +104,7 @@ pub(crate) struct DeviceIdentifier {
     name: String,
 }
 
-// FIXME: (nclack) don't want to serialize the id field.  It's unstable,
-//                 only makes sense in context of an active runtime.
-//                 Should probably drop the id's altogether except maybe for
-//                 internal debugging.
-
-// TODO: (nclack) maybe use impl_plain_old_dict for device identifier. caveat ^
+// TODO: (nclack) maybe use impl_plain_old_dict for device identifier.
 
 #[pymethods]
 impl DeviceIdentifier {
@@ -3,6 +3,7 @@ use std::{ffi::CString, ptr::NonNull, sync::Arc};
 use crate::{
     capi,
     device::{DeviceIdentifier, DeviceKind},
+    error::{clear_errors, ApiCall, ErrorKind},
     runtime::RawRuntime,
     Status,
 };
@@ -31,7 +32,8 @@ impl DeviceManager {
                 kind: capi::DeviceKind_DeviceKind_Unknown,
                 name: [0; 256],
             };
-            unsafe { capi::device_manager_get(&mut ident, dm.as_ptr(), idevice) }.ok()?;
+            ApiCall::new("device_manager_get", ErrorKind::DeviceNotFound)
+                .run(|| unsafe { capi::device_manager_get(&mut ident, dm.as_ptr(), idevice) })?;
             Ok(ident.try_into()?)
         }
 
@@ -41,9 +43,16 @@ impl DeviceManager {
             .collect()
     }
 
-    fn select(&self, kind: DeviceKind, name: Option<&str>) -> PyResult<Option<DeviceIdentifier>> {
+    #[pyo3(signature = (kind, name=None, required=false))]
+    fn select(
+        &self,
+        kind: DeviceKind,
+        name: Option<&str>,
+        required: bool,
+    ) -> PyResult<Option<DeviceIdentifier>> {
         let mut ident: capi::DeviceIdentifier = unsafe { std::mem::zeroed() };
 
+        clear_errors();
         let status = match name {
             Some(name) if name.len() > 0 => unsafe {
                 let name_ = CString::new(name)?;
@@ -61,6 +70,17 @@ impl DeviceManager {
         };
         if status.is_ok() {
             Ok(Some(ident.try_into()?))
+        } else if required {
+            Err(
+                ApiCall::new("device_manager_select", ErrorKind::DeviceNotFound).error_with_reason(
+                    &format!(
+                        "found no {:?} device matching \"{}\"",
+                        kind,
+                        name.unwrap_or_default()
+                    ),
+                    status.code(),
+                ),
+            )
         } else {
             Ok(None)
         }
@@ -69,7 +89,115 @@ impl DeviceManager {
     fn select_one_of(&self, kind: DeviceKind, names: Vec<&str>) -> Option<DeviceIdentifier> {
         names
             .into_iter()
-            .filter_map(|name| self.select(kind, Some(name)).ok().flatten())
+            .filter_map(|name| self.select(kind, Some(name), false).ok().flatten())
             .next()
     }
 }
+
+/// Finds a loaded device of `ident`'s kind named exactly `name`, preferring
+/// the one with `ident`'s driver and device ids.
+unsafe fn find_by_name(
+    dm: NonNull<capi::DeviceManager>,
+    ident: &capi::DeviceIdentifier,
+    name: &str,
+) -> Option<capi::DeviceIdentifier> {
+    let mut first = None;
+    for idevice in 0..capi::device_manager_count(dm.as_ptr()) {
+        let mut found: capi::DeviceIdentifier = std::mem::zeroed();
+        if !capi::device_manager_get(&mut found, dm.as_ptr(), idevice).is_ok()
+            || found.kind != ident.kind
+            || found.name_as_string().ok().as_deref() != Some(name)
+        {
+            continue;
+        }
+        if (found.driver_id, found.device_id) == (ident.driver_id, ident.device_id) {
+            return Some(found);
+        }
+        first.get_or_insert(found);
+    }
+    first
+}
+
+/// Looks up the device named by `ident` among the loaded drivers, filling in
+/// its driver and device ids.
+///
+/// Saved identifiers don't carry ids since those depend on the order the
+/// drivers were loaded, so ids are only kept if they still refer to a device
+/// with that name. A device named exactly `name` is preferred; otherwise the
+/// name is taken as a regular expression. An empty name selects the first
+/// device of the kind. `field` names the identifier in the error raised when
+/// nothing matches.
+///
+/// # Safety
+///
+/// `dm` must point to the device manager of a live runtime.
+pub(crate) unsafe fn resolve(
+    dm: NonNull<capi::DeviceManager>,
+    ident: &mut capi::DeviceIdentifier,
+    field: &str,
+) -> PyResult<()> {
+    if ident.kind == capi::DeviceKind_DeviceKind_None {
+        return Ok(());
+    }
+    let name = ident.name_as_string()?;
+    clear_errors();
+    if !name.is_empty() {
+        if let Some(found) = find_by_name(dm, ident, &name) {
+            *ident = found;
+            return Ok(());
+        }
+    }
+    let mut found: capi::DeviceIdentifier = std::mem::zeroed();
+    let status = if name.is_empty() {
+        capi::device_manager_select_first(dm.as_ptr(), ident.kind, &mut found)
+    } else {
+        capi::device_manager_select(
+            dm.as_ptr(),
+            ident.kind,
+            ident.name.as_ptr(),
+            name.len() as _,
+            &mut found,
+        )
+    };
+    if status.is_ok() {
+        *ident = found;
+        Ok(())
+    } else {
+        let kind: Result<DeviceKind> = ident.kind.try_into();
+        let kind = kind.map_or(ident.kind.to_string(), |k| format!("{:?}", k));
+        Err(
+            ApiCall::new("device_manager_select", ErrorKind::DeviceNotFound).error_with_reason(
+                &format!(
+                    "found no {} device matching \"{}\" for {}",
+                    kind, name, field
+                ),
+                status.code(),
+            ),
+        )
+    }
+}
+
+/// Resolves the identifiers of every video stream in `props`. See
+/// `resolve()`.
+///
+/// # Safety
+///
+/// `dm` must point to the device manager of a live runtime.
+pub(crate) unsafe fn resolve_all(
+    dm: NonNull<capi::DeviceManager>,
+    props: &mut capi::AcquireProperties,
+) -> PyResult<()> {
+    for (i, stream) in props.video.iter_mut().enumerate() {
+        resolve(
+            dm,
+            &mut stream.camera.identifier,
+            &format!("video[{}].camera.identifier", i),
+        )?;
+        resolve(
+            dm,
+            &mut stream.storage.identifier,
+            &format!("video[{}].storage.identifier", i),
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,255 @@
+use pyo3::{exceptions::PyBufferError, ffi, prelude::*};
+use std::{ffi::c_void, os::raw::c_char};
+
+use crate::components::SampleType;
+
+/// `kDLCPU`
+pub(crate) const DL_CPU: i32 = 1;
+
+const DL_INT: u8 = 0;
+const DL_UINT: u8 = 1;
+const DL_FLOAT: u8 = 2;
+
+const CAPSULE_NAME: &[u8] = b"dltensor\0";
+const VERSIONED_CAPSULE_NAME: &[u8] = b"dltensor_versioned\0";
+
+/// `DLPACK_FLAG_BITMASK_READ_ONLY`
+const FLAG_READ_ONLY: u64 = 1 << 0;
+/// `DLPACK_FLAG_BITMASK_IS_COPIED`
+const FLAG_IS_COPIED: u64 = 1 << 1;
+
+// Layouts follow dlpack.h. See https://dmlc.github.io/dlpack/latest/
+
+#[repr(C)]
+struct DLDevice {
+    device_type: i32,
+    device_id: i32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct DLDataType {
+    code: u8,
+    bits: u8,
+    lanes: u16,
+}
+
+impl From<SampleType> for DLDataType {
+    fn from(sample_type: SampleType) -> Self {
+        let (code, bits) = match sample_type {
+            SampleType::U8 => (DL_UINT, 8),
+            SampleType::U16 | SampleType::U10 | SampleType::U12 | SampleType::U14 => (DL_UINT, 16),
+            SampleType::I8 => (DL_INT, 8),
+            SampleType::I16 => (DL_INT, 16),
+            SampleType::F32 => (DL_FLOAT, 32),
+        };
+        Self {
+            code,
+            bits,
+            lanes: 1,
+        }
+    }
+}
+
+#[repr(C)]
+struct DLTensor {
+    data: *mut c_void,
+    device: DLDevice,
+    ndim: i32,
+    dtype: DLDataType,
+    shape: *mut i64,
+    strides: *mut i64,
+    byte_offset: u64,
+}
+
+#[repr(C)]
+struct DLManagedTensor {
+    dl_tensor: DLTensor,
+    manager_ctx: *mut c_void,
+    deleter: Option<unsafe extern "C" fn(*mut DLManagedTensor)>,
+}
+
+#[repr(C)]
+struct DLPackVersion {
+    major: u32,
+    minor: u32,
+}
+
+#[repr(C)]
+struct DLManagedTensorVersioned {
+    version: DLPackVersion,
+    manager_ctx: *mut c_void,
+    deleter: Option<unsafe extern "C" fn(*mut DLManagedTensorVersioned)>,
+    flags: u64,
+    dl_tensor: DLTensor,
+}
+
+/// Owns the shape and strides of an exported tensor, and keeps the memory it
+/// points to alive.
+struct ManagerContext {
+    shape: [i64; 4],
+    strides: [i64; 4],
+    _owner: PyObject,
+}
+
+/// Releases the owner right away rather than whenever the GIL is next taken,
+/// since consumers may call deleters from any thread.
+unsafe fn delete_manager_context(ctx: *mut c_void) {
+    let ctx = Box::from_raw(ctx as *mut ManagerContext);
+    Python::with_gil(move |_| drop(ctx));
+}
+
+unsafe extern "C" fn delete_managed_tensor(tensor: *mut DLManagedTensor) {
+    let tensor = Box::from_raw(tensor);
+    delete_manager_context(tensor.manager_ctx);
+}
+
+unsafe extern "C" fn delete_managed_tensor_versioned(tensor: *mut DLManagedTensorVersioned) {
+    let tensor = Box::from_raw(tensor);
+    delete_manager_context(tensor.manager_ctx);
+}
+
+unsafe extern "C" fn destroy_capsule(capsule: *mut ffi::PyObject) {
+    // Consumers rename the capsule once they take ownership of the tensor.
+    let name = CAPSULE_NAME.as_ptr() as *const c_char;
+    if ffi::PyCapsule_IsValid(capsule, name) == 1 {
+        let tensor = ffi::PyCapsule_GetPointer(capsule, name) as *mut DLManagedTensor;
+        if let Some(deleter) = (*tensor).deleter {
+            deleter(tensor);
+        }
+    }
+}
+
+unsafe extern "C" fn destroy_versioned_capsule(capsule: *mut ffi::PyObject) {
+    let name = VERSIONED_CAPSULE_NAME.as_ptr() as *const c_char;
+    if ffi::PyCapsule_IsValid(capsule, name) == 1 {
+        let tensor = ffi::PyCapsule_GetPointer(capsule, name) as *mut DLManagedTensorVersioned;
+        if let Some(deleter) = (*tensor).deleter {
+            deleter(tensor);
+        }
+    }
+}
+
+/// How a `__dlpack__()` call is served.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Request {
+    /// Whether the consumer accepts a `DLManagedTensorVersioned`.
+    versioned: bool,
+    /// Whether to export a copy of the frame rather than its memory.
+    pub(crate) copy: bool,
+}
+
+/// Wraps a `(planes, height, width, channels)` array at `data` in a DLPack
+/// capsule. `strides` are in samples.
+///
+/// Memory that isn't a copy is exported read-only, which only versioned
+/// tensors can express. `check_request()` makes sure unversioned ones are
+/// copies.
+///
+/// # Safety
+///
+/// `owner` must keep the memory at `data` alive.
+pub(crate) unsafe fn to_capsule(
+    py: Python<'_>,
+    data: *mut c_void,
+    shape: [usize; 4],
+    strides: [usize; 4],
+    dtype: DLDataType,
+    owner: PyObject,
+    request: Request,
+) -> PyResult<PyObject> {
+    let shape = shape.map(|n| n as i64);
+    let strides = strides.map(|n| n as i64);
+    let mut ctx = Box::new(ManagerContext {
+        shape,
+        strides,
+        _owner: owner,
+    });
+    let dl_tensor = DLTensor {
+        data,
+        device: DLDevice {
+            device_type: DL_CPU,
+            device_id: 0,
+        },
+        ndim: 4,
+        dtype,
+        shape: ctx.shape.as_mut_ptr(),
+        strides: ctx.strides.as_mut_ptr(),
+        byte_offset: 0,
+    };
+    let manager_ctx = Box::into_raw(ctx) as *mut c_void;
+
+    let capsule = if request.versioned {
+        let tensor = Box::into_raw(Box::new(DLManagedTensorVersioned {
+            version: DLPackVersion { major: 1, minor: 0 },
+            manager_ctx,
+            deleter: Some(delete_managed_tensor_versioned),
+            flags: if request.copy {
+                FLAG_IS_COPIED
+            } else {
+                FLAG_READ_ONLY
+            },
+            dl_tensor,
+        }));
+        let capsule = ffi::PyCapsule_New(
+            tensor as *mut c_void,
+            VERSIONED_CAPSULE_NAME.as_ptr() as *const c_char,
+            Some(destroy_versioned_capsule),
+        );
+        if capsule.is_null() {
+            delete_managed_tensor_versioned(tensor);
+        }
+        capsule
+    } else {
+        let tensor = Box::into_raw(Box::new(DLManagedTensor {
+            dl_tensor,
+            manager_ctx,
+            deleter: Some(delete_managed_tensor),
+        }));
+        let capsule = ffi::PyCapsule_New(
+            tensor as *mut c_void,
+            CAPSULE_NAME.as_ptr() as *const c_char,
+            Some(destroy_capsule),
+        );
+        if capsule.is_null() {
+            delete_managed_tensor(tensor);
+        }
+        capsule
+    };
+    if capsule.is_null() {
+        return Err(PyErr::fetch(py));
+    }
+    Ok(PyObject::from_owned_ptr(py, capsule))
+}
+
+/// Checks the arguments of a `__dlpack__()` call against what frames can
+/// provide.
+///
+/// Frames are exported in place only to consumers that accept read-only
+/// tensors, which requires DLPack 1.0. Others get a copy, unless they ask for
+/// none.
+pub(crate) fn check_request(
+    dl_device: Option<(i32, i32)>,
+    max_version: Option<(u32, u32)>,
+    copy: Option<bool>,
+) -> PyResult<Request> {
+    if let Some((device_type, _)) = dl_device {
+        if device_type != DL_CPU {
+            return Err(PyBufferError::new_err(
+                "Frames can only be exported to the CPU",
+            ));
+        }
+    }
+    let versioned = max_version.map_or(false, |(major, _)| major >= 1);
+    let copy = match copy {
+        Some(copy) if versioned => copy,
+        Some(false) => {
+            return Err(PyBufferError::new_err(
+                "Frames can only be exported without copying as read-only \
+                 tensors, which requires max_version=(1, 0) or later",
+            ))
+        }
+        Some(true) | None => !versioned,
+    };
+    Ok(Request { versioned, copy })
+}
@@ -0,0 +1,213 @@
+use pyo3::{
+    create_exception,
+    exceptions::{PyRuntimeError, PyTimeoutError},
+    prelude::*,
+    sync::GILOnceCell,
+    types::{PyDict, PyTuple, PyType},
+};
+use std::{cell::RefCell, collections::VecDeque};
+
+use crate::Status;
+
+create_exception!(
+    acquire,
+    AcquireError,
+    PyRuntimeError,
+    "Raised when a call into the core runtime fails."
+);
+create_exception!(
+    acquire,
+    ConfigurationError,
+    AcquireError,
+    "Raised when the runtime rejects a configuration."
+);
+create_exception!(
+    acquire,
+    DeviceNotFoundError,
+    AcquireError,
+    "Raised when a requested device could not be found."
+);
+create_exception!(
+    acquire,
+    StateError,
+    AcquireError,
+    "Raised when the runtime is not in a state that allows the request."
+);
+create_exception!(
+    acquire,
+    StorageError,
+    AcquireError,
+    "Raised when storage properties could not be set up."
+);
+
+static ACQUIRE_TIMEOUT_ERROR: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+
+/// `AcquireTimeoutError`, which derives from the builtin `TimeoutError` as
+/// well as `AcquireError` so that `except TimeoutError` catches it.
+/// `create_exception!` only takes one base, so the class is made by calling
+/// `type`.
+fn acquire_timeout_error(py: Python<'_>) -> PyResult<&PyType> {
+    let ty = ACQUIRE_TIMEOUT_ERROR.get_or_try_init(py, || -> PyResult<_> {
+        let bases = PyTuple::new(
+            py,
+            [
+                py.get_type::<AcquireError>(),
+                py.get_type::<PyTimeoutError>(),
+            ],
+        );
+        let dict = PyDict::new(py);
+        dict.set_item("__module__", "acquire")?;
+        dict.set_item(
+            "__doc__",
+            "Raised when the runtime did not respond in time.",
+        )?;
+        let ty = py
+            .get_type::<PyType>()
+            .call1(("AcquireTimeoutError", bases, dict))?
+            .downcast::<PyType>()?;
+        Ok(ty.into())
+    })?;
+    Ok(ty.as_ref(py))
+}
+
+/// The maximum number of error lines from the core runtime that are kept
+/// for attaching to exceptions.
+const MAX_RECENT_ERRORS: usize = 16;
+
+thread_local! {
+    /// Error lines reported by the core runtime on this thread since the
+    /// current api call began.
+    ///
+    /// The core runtime reports errors on the thread that made the failing
+    /// call, so keeping them per thread stops errors from another runtime, or
+    /// from a runtime's own acquisition threads, ending up on an unrelated
+    /// exception.
+    static RECENT_ERRORS: RefCell<VecDeque<String>> = RefCell::new(VecDeque::new());
+}
+
+/// Remembers an error line reported by the core runtime so that it can be
+/// attached to the exception raised by the call in progress.
+pub(crate) fn record_error(line: String) {
+    RECENT_ERRORS.with(|errors| {
+        let mut errors = errors.borrow_mut();
+        if errors.len() == MAX_RECENT_ERRORS {
+            errors.pop_front();
+        }
+        errors.push_back(line);
+    })
+}
+
+/// Forgets the error lines reported so far on this thread, at the start of a
+/// call whose failure is reported with `ApiCall::error_with_reason()`.
+pub(crate) fn clear_errors() {
+    RECENT_ERRORS.with(|errors| errors.borrow_mut().clear())
+}
+
+fn take_errors() -> Vec<String> {
+    RECENT_ERRORS.with(|errors| errors.borrow_mut().drain(..).collect())
+}
+
+/// Which `AcquireError` subclass a failed call raises.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum ErrorKind {
+    Other,
+    Configuration,
+    DeviceNotFound,
+    State,
+    Storage,
+    Timeout,
+}
+
+/// Describes a call into the core api so failures can be reported with
+/// context.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ApiCall {
+    api: &'static str,
+    kind: ErrorKind,
+    stream_id: Option<u32>,
+}
+
+impl ApiCall {
+    pub(crate) const fn new(api: &'static str, kind: ErrorKind) -> Self {
+        Self {
+            api,
+            kind,
+            stream_id: None,
+        }
+    }
+
+    pub(crate) const fn stream(self, stream_id: u32) -> Self {
+        Self {
+            stream_id: Some(stream_id),
+            ..self
+        }
+    }
+
+    /// Makes the call with `f`, raising the matching `AcquireError` if the
+    /// returned status isn't ok.
+    ///
+    /// Only error lines reported while `f` runs are attached to the
+    /// exception.
+    pub(crate) fn run<T: Status>(self, f: impl FnOnce() -> T) -> anyhow::Result<T> {
+        clear_errors();
+        f().ok(self)
+    }
+
+    /// Builds the exception for a failure of this call.
+    ///
+    /// Takes the error lines reported by the core runtime on this thread
+    /// since the call began.
+    pub(crate) fn error(&self, status_code: Option<u32>) -> PyErr {
+        self.error_with_reason("failed", status_code)
+    }
+
+    /// Like `error()`, but describes the failure with `reason` instead of
+    /// "failed".
+    pub(crate) fn error_with_reason(&self, reason: &str, status_code: Option<u32>) -> PyErr {
+        let messages = take_errors();
+
+        let mut message = format!("{} {}", self.api, reason);
+        if let Some(stream_id) = self.stream_id {
+            message = format!("{} for stream {}", message, stream_id);
+        }
+        if let Some(last) = messages.last() {
+            message = format!("{}: {}", message, last);
+        }
+
+        Python::with_gil(|py| {
+            let err = match self.kind {
+                ErrorKind::Other => AcquireError::new_err(message),
+                ErrorKind::Configuration => ConfigurationError::new_err(message),
+                ErrorKind::DeviceNotFound => DeviceNotFoundError::new_err(message),
+                ErrorKind::State => StateError::new_err(message),
+                ErrorKind::Storage => StorageError::new_err(message),
+                ErrorKind::Timeout => match acquire_timeout_error(py) {
+                    Ok(ty) => PyErr::from_type(ty, message),
+                    Err(e) => return e,
+                },
+            };
+            let value = err.value(py);
+            let annotate = || -> PyResult<()> {
+                value.setattr("api", self.api)?;
+                value.setattr("stream_id", self.stream_id)?;
+                value.setattr("status_code", status_code)?;
+                value.setattr("messages", messages)?;
+                Ok(())
+            };
+            match annotate() {
+                Ok(()) => err,
+                Err(e) => e,
+            }
+        })
+    }
+}
+
+pub(crate) fn add_exceptions(py: Python<'_>, m: &PyModule) -> PyResult<()> {
+    m.add("AcquireError", py.get_type::<AcquireError>())?;
+    m.add("ConfigurationError", py.get_type::<ConfigurationError>())?;
+    m.add("DeviceNotFoundError", py.get_type::<DeviceNotFoundError>())?;
+    m.add("StateError", py.get_type::<StateError>())?;
+    m.add("StorageError", py.get_type::<StorageError>())?;
+    m.add("AcquireTimeoutError", acquire_timeout_error(py)?)?;
+    Ok(())
+}
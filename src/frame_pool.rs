@@ -0,0 +1,245 @@
+use parking_lot::Mutex;
+use std::{
+    ptr::NonNull,
+    sync::{Arc, Weak},
+};
+
+use crate::capi;
+
+/// The most memory idle buffers hold before more are freed rather than kept
+/// for reuse.
+const MAX_IDLE_BYTES: usize = 64 << 20;
+
+/// Reusable buffers for frames copied out of the video queues.
+pub(crate) struct FramePool {
+    idle: Mutex<Idle>,
+    max_idle_bytes: usize,
+}
+
+#[derive(Default)]
+struct Idle {
+    buffers: Vec<Vec<u64>>,
+    /// The capacity of `buffers`, in bytes.
+    bytes: usize,
+    /// Bumped by `clear()`, so buffers handed out before then are freed
+    /// rather than returned.
+    generation: u64,
+}
+
+impl Default for FramePool {
+    fn default() -> Self {
+        Self::with_max_idle_bytes(MAX_IDLE_BYTES)
+    }
+}
+
+impl FramePool {
+    fn with_max_idle_bytes(max_idle_bytes: usize) -> Self {
+        Self {
+            idle: Default::default(),
+            max_idle_bytes,
+        }
+    }
+
+    /// Frees the idle buffers, and the buffers in use once they're dropped.
+    ///
+    /// Called when frames may change size, so buffers sized for the old
+    /// frames aren't kept around.
+    pub(crate) fn clear(&self) {
+        let mut idle = self.idle.lock();
+        idle.generation += 1;
+        idle.bytes = 0;
+        let buffers = std::mem::take(&mut idle.buffers);
+        drop(idle);
+        drop(buffers);
+    }
+
+    /// Copies the frame at `frame`, header included, into a buffer from the
+    /// pool.
+    ///
+    /// # Safety
+    ///
+    /// `frame` must point to a complete frame of `bytes_of_frame` bytes.
+    pub(crate) unsafe fn copy_frame(
+        self: &Arc<Self>,
+        frame: *const capi::VideoFrame,
+    ) -> FrameBuffer {
+        let nbytes = std::ptr::read_unaligned(frame).bytes_of_frame as usize;
+        // Words rather than bytes, so the header is aligned. Always room for
+        // a whole header, even if the frame claims to be smaller.
+        let nwords = (nbytes.max(std::mem::size_of::<capi::VideoFrame>()) + 7) / 8;
+
+        let (mut words, generation) = {
+            let mut idle = self.idle.lock();
+            // The smallest buffer that fits.
+            let found = (idle.buffers.iter().enumerate())
+                .filter(|(_, b)| b.capacity() >= nwords)
+                .min_by_key(|(_, b)| b.capacity())
+                .map(|(i, _)| i);
+            let words = match found {
+                Some(i) => {
+                    let words = idle.buffers.swap_remove(i);
+                    idle.bytes -= words.capacity() * 8;
+                    words
+                }
+                None => Vec::with_capacity(nwords),
+            };
+            (words, idle.generation)
+        };
+        words.clear();
+        words.resize(nwords, 0);
+        std::ptr::copy_nonoverlapping(frame as *const u8, words.as_mut_ptr() as *mut u8, nbytes);
+        FrameBuffer {
+            words,
+            generation,
+            pool: Arc::downgrade(self),
+        }
+    }
+
+    /// Keeps `words` for reuse, unless the pool was cleared since it was
+    /// handed out or is full.
+    fn put(&self, words: Vec<u64>, generation: u64) {
+        let nbytes = words.capacity() * 8;
+        let mut idle = self.idle.lock();
+        if generation != idle.generation {
+            return;
+        }
+        // Free the largest buffers until the returned one fits.
+        while idle.bytes + nbytes > self.max_idle_bytes {
+            let largest = (idle.buffers.iter().enumerate())
+                .max_by_key(|(_, b)| b.capacity())
+                .map(|(i, b)| (i, b.capacity()));
+            match largest {
+                Some((i, capacity)) if capacity > words.capacity() => {
+                    idle.buffers.swap_remove(i);
+                    idle.bytes -= capacity * 8;
+                }
+                _ => return,
+            }
+        }
+        idle.bytes += nbytes;
+        idle.buffers.push(words);
+    }
+}
+
+/// A copy of a frame that goes back to its pool when dropped.
+pub(crate) struct FrameBuffer {
+    words: Vec<u64>,
+    /// The pool's generation when the buffer was handed out.
+    generation: u64,
+    pool: Weak<FramePool>,
+}
+
+impl FrameBuffer {
+    pub(crate) fn header(&self) -> NonNull<capi::VideoFrame> {
+        NonNull::new(self.words.as_ptr() as *mut capi::VideoFrame)
+            .expect("Vec pointers are never null")
+    }
+}
+
+impl Clone for FrameBuffer {
+    /// Copies the frame into another buffer from the same pool.
+    fn clone(&self) -> Self {
+        match self.pool.upgrade() {
+            Some(pool) => unsafe { pool.copy_frame(self.header().as_ptr()) },
+            None => FrameBuffer {
+                words: self.words.clone(),
+                generation: 0,
+                pool: Weak::new(),
+            },
+        }
+    }
+}
+
+impl Drop for FrameBuffer {
+    fn drop(&mut self) {
+        if let Some(pool) = self.pool.upgrade() {
+            pool.put(std::mem::take(&mut self.words), self.generation);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A frame of `nbytes`, header included, filled with `fill` after the
+    /// header.
+    fn frame(nbytes: usize, fill: u8) -> Vec<u64> {
+        let mut words = vec![0u64; (nbytes + 7) / 8];
+        let header = std::mem::size_of::<capi::VideoFrame>();
+        let bytes =
+            unsafe { std::slice::from_raw_parts_mut(words.as_mut_ptr() as *mut u8, nbytes) };
+        bytes[header..].fill(fill);
+        let mut frame: capi::VideoFrame = unsafe { std::mem::zeroed() };
+        frame.bytes_of_frame = nbytes as _;
+        unsafe { std::ptr::write_unaligned(words.as_mut_ptr() as *mut capi::VideoFrame, frame) };
+        words
+    }
+
+    fn copy(pool: &Arc<FramePool>, frame: &[u64]) -> FrameBuffer {
+        unsafe { pool.copy_frame(frame.as_ptr() as *const capi::VideoFrame) }
+    }
+
+    fn idle(pool: &FramePool) -> (usize, usize) {
+        let idle = pool.idle.lock();
+        (idle.buffers.len(), idle.bytes)
+    }
+
+    #[test]
+    fn reuses_returned_buffers() {
+        let pool = Arc::new(FramePool::default());
+        let nbytes = std::mem::size_of::<capi::VideoFrame>() + 1024;
+        let first = copy(&pool, &frame(nbytes, 1));
+        let ptr = first.header();
+        drop(first);
+        assert_eq!(idle(&pool).0, 1);
+
+        let second = copy(&pool, &frame(nbytes, 2));
+        assert_eq!(second.header(), ptr);
+        assert_eq!(idle(&pool), (0, 0));
+        assert_eq!(second.words, frame(nbytes, 2));
+    }
+
+    #[test]
+    fn frees_buffers_past_the_byte_bound() {
+        let nbytes = std::mem::size_of::<capi::VideoFrame>() + 1024;
+        let pool = Arc::new(FramePool::with_max_idle_bytes(2 * nbytes));
+        let buffers: Vec<_> = (0..3).map(|_| copy(&pool, &frame(nbytes, 0))).collect();
+        drop(buffers);
+        let (count, bytes) = idle(&pool);
+        assert_eq!(count, 2);
+        assert!(bytes <= 2 * nbytes);
+    }
+
+    #[test]
+    fn frees_larger_buffers_to_make_room() {
+        let small = std::mem::size_of::<capi::VideoFrame>() + 64;
+        let large = std::mem::size_of::<capi::VideoFrame>() + 4096;
+        let pool = Arc::new(FramePool::with_max_idle_bytes(large + 8));
+        let a = copy(&pool, &frame(large, 0));
+        let b = copy(&pool, &frame(small, 0));
+        drop(a);
+        drop(b);
+        let idle = pool.idle.lock();
+        assert_eq!(idle.buffers.len(), 1);
+        assert!(idle.buffers[0].capacity() * 8 < large);
+    }
+
+    #[test]
+    fn frees_buffers_handed_out_before_clear() {
+        let pool = Arc::new(FramePool::default());
+        let nbytes = std::mem::size_of::<capi::VideoFrame>() + 1024;
+        drop(copy(&pool, &frame(nbytes, 0)));
+        let held = copy(&pool, &frame(nbytes, 0));
+        drop(copy(&pool, &frame(nbytes, 0)));
+        assert_eq!(idle(&pool).0, 1);
+
+        pool.clear();
+        assert_eq!(idle(&pool), (0, 0));
+        drop(held);
+        assert_eq!(idle(&pool), (0, 0));
+
+        drop(copy(&pool, &frame(nbytes, 0)));
+        assert_eq!(idle(&pool).0, 1);
+    }
+}
@@ -2,29 +2,47 @@ pub(crate) mod camera;
 pub(crate) mod capabilities;
 pub(crate) mod capi;
 pub(crate) mod components;
+pub(crate) mod config_diff;
+pub(crate) mod config_file;
+pub(crate) mod config_path;
 pub(crate) mod core_properties;
 pub(crate) mod device;
 pub(crate) mod device_manager;
+pub(crate) mod dlpack;
+pub(crate) mod error;
+pub(crate) mod frame_pool;
+pub(crate) mod log_buffer;
 pub(crate) mod runtime;
 pub(crate) mod storage;
+pub(crate) mod stream_stats;
+pub(crate) mod strict;
+pub(crate) mod validation;
 
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use device_manager::DeviceManager;
+use error::ApiCall;
 use pyo3::prelude::*;
 use std::ffi::CStr;
 
 use crate::runtime::{
-    AvailableData, AvailableDataContext, VideoFrame, VideoFrameMetadata, VideoFrameTimestamps,
+    AvailableData, AvailableDataContext, ClockCorrelation, FrameDataGuard, FrameShapeInfo,
+    FrameStream, OwnedVideoFrame, StateChangeCause, VideoFrame, VideoFrameMetadata,
+    VideoFrameTimestamps, WaitStatus,
 };
 
 trait Status: Copy + Sized {
     fn is_ok(&self) -> bool;
 
-    fn ok(&self) -> Result<Self> {
+    /// The raw status code, if there is one.
+    fn code(&self) -> Option<u32>;
+
+    /// Checks the status returned by `call`, raising the matching
+    /// `AcquireError` on failure.
+    fn ok(&self, call: ApiCall) -> Result<Self> {
         if self.is_ok() {
             Ok(*self)
         } else {
-            Err(anyhow!("Failed acquire api status check")) // TODO: (nclack) adapt to thiserror maybe, need to know which api called, params etc
+            Err(call.error(self.code()).into())
         }
     }
 }
@@ -33,12 +51,20 @@ impl Status for capi::DeviceStatusCode {
     fn is_ok(&self) -> bool {
         *self == capi::AcquireStatusCode_AcquireStatus_Ok
     }
+
+    fn code(&self) -> Option<u32> {
+        Some(*self as u32)
+    }
 }
 
 impl Status for bool {
     fn is_ok(&self) -> bool {
         *self
     }
+
+    fn code(&self) -> Option<u32> {
+        None
+    }
 }
 
 #[pyfunction]
@@ -63,9 +89,24 @@ fn acquire(py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<DeviceManager>()?;
     m.add_class::<AvailableData>()?;
     m.add_class::<AvailableDataContext>()?;
+    m.add_class::<FrameStream>()?;
     m.add_class::<VideoFrame>()?;
+    m.add_class::<OwnedVideoFrame>()?;
+    m.add_class::<FrameDataGuard>()?;
     m.add_class::<VideoFrameMetadata>()?;
+    m.add_class::<FrameShapeInfo>()?;
     m.add_class::<VideoFrameTimestamps>()?;
+    m.add_class::<ClockCorrelation>()?;
+    m.add_class::<WaitStatus>()?;
+    m.add_class::<StateChangeCause>()?;
+    m.add_class::<stream_stats::StreamStats>()?;
+    m.add_class::<stream_stats::FrameIdAnomaly>()?;
+    m.add_class::<stream_stats::FrameIdAnomalyKind>()?;
+    m.add_class::<log_buffer::LogLevel>()?;
+    m.add_class::<log_buffer::LogRecord>()?;
+    m.add_class::<config_diff::ConfigurationChange>()?;
+    m.add_class::<validation::ConfigurationViolation>()?;
+    m.add_class::<validation::ViolationKind>()?;
 
     m.add_class::<core_properties::Properties>()?;
     m.add_class::<core_properties::VideoStream>()?;
@@ -105,6 +146,8 @@ fn acquire(py: Python, m: &PyModule) -> PyResult<()> {
 
     m.add_class::<device::DeviceKind>()?;
 
+    error::add_exceptions(py, m)?;
+
     m.add_function(wrap_pyfunction!(core_api_version, m)?)?;
     Ok(())
 }
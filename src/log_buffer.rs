@@ -0,0 +1,123 @@
+use parking_lot::Mutex;
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Weak},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::components::macros::impl_plain_old_dict;
+
+/// The maximum number of records kept by each runtime's log buffer.
+const LOG_BUFFER_CAPACITY: usize = 1024;
+
+/// Severity of a message reported by the core runtime.
+#[pyclass]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LogLevel {
+    Debug,
+    Error,
+}
+
+/// A message reported by the core runtime or one of its drivers.
+#[pyclass]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogRecord {
+    #[pyo3(get)]
+    pub(crate) level: LogLevel,
+
+    #[pyo3(get)]
+    pub(crate) file: String,
+
+    #[pyo3(get)]
+    pub(crate) line: i32,
+
+    #[pyo3(get)]
+    pub(crate) function: String,
+
+    #[pyo3(get)]
+    pub(crate) message: String,
+
+    /// Seconds since the unix epoch.
+    #[pyo3(get)]
+    pub(crate) timestamp: f64,
+}
+
+impl_plain_old_dict!(@out LogRecord);
+
+impl LogRecord {
+    pub(crate) fn new(
+        level: LogLevel,
+        file: String,
+        line: i32,
+        function: String,
+        message: String,
+    ) -> Self {
+        Self {
+            level,
+            file,
+            line,
+            function,
+            message,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0.0, |t| t.as_secs_f64()),
+        }
+    }
+}
+
+/// Buffers of the live runtimes.
+///
+/// The core runtime's reporter callback has no user data, so every message is
+/// delivered to every live buffer.
+static LOG_BUFFERS: Mutex<Vec<Weak<LogBuffer>>> = parking_lot::const_mutex(Vec::new());
+
+/// Bounded ring buffer holding the most recent messages from the core
+/// runtime.
+#[derive(Default)]
+pub(crate) struct LogBuffer {
+    records: Mutex<VecDeque<LogRecord>>,
+}
+
+impl LogBuffer {
+    /// Creates a buffer that receives messages until it is dropped.
+    pub(crate) fn register() -> Arc<Self> {
+        let buffer = Arc::new(Self::default());
+        let mut buffers = LOG_BUFFERS.lock();
+        buffers.retain(|b| b.strong_count() > 0);
+        buffers.push(Arc::downgrade(&buffer));
+        buffer
+    }
+
+    fn push(&self, record: LogRecord) {
+        let mut records = self.records.lock();
+        if records.len() == LOG_BUFFER_CAPACITY {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    /// Returns the buffered records, oldest first.
+    ///
+    /// When `since` is set, only records with a later timestamp are returned.
+    pub(crate) fn records(&self, since: Option<f64>) -> Vec<LogRecord> {
+        self.records
+            .lock()
+            .iter()
+            .filter(|r| since.map_or(true, |since| r.timestamp > since))
+            .cloned()
+            .collect()
+    }
+
+    pub(crate) fn clear(&self) {
+        self.records.lock().clear();
+    }
+}
+
+/// Delivers a record to the buffer of every live runtime.
+pub(crate) fn dispatch(record: LogRecord) {
+    for buffer in LOG_BUFFERS.lock().iter().filter_map(Weak::upgrade) {
+        buffer.push(record.clone());
+    }
+}
@@ -1,23 +1,41 @@
 use anyhow::{anyhow, Result};
 use log::{debug, error};
 use numpy::{
-    ndarray::{Dim, IntoDimension, RawArrayView},
-    Ix4, ToPyArray,
+    ndarray::{Array4, Dim, Dimension, IntoDimension, RawArrayView, RawArrayViewMut, ShapeBuilder},
+    Element, IntoPyArray, Ix4, PyArray, PyArray1, PyArray5, ToPyArray,
 };
 use parking_lot::Mutex;
-use pyo3::exceptions::PyRuntimeError;
+use pyo3::exceptions::{
+    PyBufferError, PyIndexError, PyRuntimeError, PyStopAsyncIteration, PyTypeError, PyValueError,
+};
 use pyo3::prelude::*;
+use pyo3::types::{PyCFunction, PyDict, PySlice};
+use pyo3::{PyTraverseError, PyVisit};
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::HashMap,
     ffi::CStr,
     ptr::{null_mut, NonNull},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc, Arc, Weak,
+    },
+    time::{Duration, Instant},
 };
 
 use crate::capabilities::Capabilities;
 use crate::{
-    capi, components::macros::impl_plain_old_dict, core_properties::Properties,
-    device::DeviceState, device_manager, Status,
+    capi,
+    components::{macros::impl_plain_old_dict, SampleType},
+    config_diff, config_path,
+    core_properties::Properties,
+    device::DeviceState,
+    device_manager, dlpack,
+    error::{clear_errors, record_error, ApiCall, ErrorKind},
+    frame_pool::{FrameBuffer, FramePool},
+    log_buffer::{self, LogBuffer, LogLevel, LogRecord},
+    stream_stats::{FrameIdAnomaly, StreamCounters, StreamStats},
+    validation::{self, ConfigurationViolation},
 };
 
 unsafe extern "C" fn reporter(
@@ -40,13 +58,50 @@ unsafe extern "C" fn reporter(
     let msg = as_string(msg);
     if is_error > 0 {
         error!("{}:{} - {}(): {}", file, line, function, msg);
+        record_error(format!("{}:{} - {}(): {}", file, line, function, msg));
     } else {
         debug!("{}:{} - {}(): {}", file, line, function, msg);
     }
+    let level = if is_error > 0 {
+        LogLevel::Error
+    } else {
+        LogLevel::Debug
+    };
+    log_buffer::dispatch(LogRecord::new(level, file, line, function, msg));
 }
 
 pub(crate) struct RawRuntime {
     inner: NonNull<capi::AcquireRuntime>,
+    /// Counters for each stream, reset when the runtime starts.
+    stats: Mutex<HashMap<u32, StreamCounters>>,
+    /// Log a warning for each frame id anomaly.
+    warn_on_frame_id_anomalies: AtomicBool,
+    /// Host clocks sampled the last time the runtime was started.
+    clock_correlation: Mutex<Option<ClockCorrelation>>,
+    /// Buffers for frames detached from the video queues.
+    frame_pool: Arc<FramePool>,
+    /// How far each stream has been read.
+    read_states: Mutex<HashMap<u32, ReadState>>,
+    /// The number of times the runtime was started.
+    starts: AtomicU64,
+}
+
+/// How far a stream has been read, beyond what was released to the core.
+#[derive(Debug, Default)]
+struct ReadState {
+    /// Whether a region of the stream is mapped.
+    ///
+    /// Mapping a stream again returns the same frames until the first
+    /// region is unmapped, and unmapping both would release them twice, so
+    /// a stream isn't mapped again until its region is unmapped.
+    is_mapped: bool,
+    /// Bytes at the start of the stream's queue that were consumed but are
+    /// still borrowed, e.g. by an array from `VideoFrame.data(copy=False)`.
+    /// They aren't released to the core until nothing borrows them, so that
+    /// their memory isn't reused, and are skipped when the stream is mapped.
+    held_bytes: usize,
+    /// Unmapped regions whose frames are still borrowed.
+    borrowed_regions: usize,
 }
 
 unsafe impl Send for RawRuntime {}
@@ -56,57 +111,201 @@ impl RawRuntime {
     fn new() -> Result<Self> {
         Ok(Self {
             inner: NonNull::new(unsafe { capi::acquire_init(Some(reporter)) })
-                .ok_or(anyhow!("Failed to initialize core runtime."))?,
+                .ok_or_else(|| ApiCall::new("acquire_init", ErrorKind::Other).error(None))?,
+            stats: Default::default(),
+            warn_on_frame_id_anomalies: AtomicBool::new(false),
+            clock_correlation: Default::default(),
+            frame_pool: Default::default(),
+            read_states: Default::default(),
+            starts: AtomicU64::new(0),
         })
     }
 
     fn start(&self) -> Result<()> {
         debug!("START Runtime");
-        unsafe { capi::acquire_start(self.inner.as_ptr()) }.ok()?;
+        // Starting may reuse the memory of frames that are still borrowed.
+        let borrowed = self
+            .read_states
+            .lock()
+            .iter()
+            .filter(|(_, state)| state.borrowed_regions > 0)
+            .map(|(&stream_id, _)| stream_id)
+            .min();
+        if let Some(stream_id) = borrowed {
+            return Err(ApiCall::new("acquire_start", ErrorKind::State)
+                .error_with_reason(
+                    &format!(
+                        "was skipped: frames from stream {} are still borrowed, e.g. by an \
+                         array from VideoFrame.data(copy=False) or a DLPack tensor. Delete \
+                         them, or copy the frames with data() or detach(), before starting",
+                        stream_id
+                    ),
+                    None,
+                )
+                .into());
+        }
+        self.stats.lock().clear();
+        ApiCall::new("acquire_start", ErrorKind::State)
+            .run(|| unsafe { capi::acquire_start(self.inner.as_ptr()) })?;
+        self.starts.fetch_add(1, Ordering::Release);
         debug!("START Runtime OK");
         Ok(())
     }
 
     fn execute_trigger(&self, stream_id: u32) -> Result<()> {
-        unsafe { capi::acquire_execute_trigger(self.inner.as_ptr(), stream_id) }.ok()?;
+        ApiCall::new("acquire_execute_trigger", ErrorKind::State)
+            .stream(stream_id)
+            .run(|| unsafe { capi::acquire_execute_trigger(self.inner.as_ptr(), stream_id) })?;
         Ok(())
     }
 
     fn stop(&self) -> Result<()> {
-        unsafe { capi::acquire_stop(self.inner.as_ptr()) }.ok()?;
+        ApiCall::new("acquire_stop", ErrorKind::State)
+            .run(|| unsafe { capi::acquire_stop(self.inner.as_ptr()) })?;
+        // The next acquisition may have frames of a different size.
+        self.frame_pool.clear();
         Ok(())
     }
 
     fn abort(&self) -> Result<()> {
-        unsafe { capi::acquire_abort(self.inner.as_ptr()) }.ok()?;
+        ApiCall::new("acquire_abort", ErrorKind::State)
+            .run(|| unsafe { capi::acquire_abort(self.inner.as_ptr()) })?;
+        // The next acquisition may have frames of a different size.
+        self.frame_pool.clear();
         Ok(())
     }
 
+    fn get_state(&self) -> Result<DeviceState> {
+        unsafe { capi::acquire_get_state(self.inner.as_ptr()) }.try_into()
+    }
+
+    fn device_manager(&self) -> Result<NonNull<capi::DeviceManager>> {
+        NonNull::new(unsafe { capi::acquire_device_manager(self.inner.as_ptr()) as _ })
+            .ok_or(anyhow!("Failed to get device manager"))
+    }
+
+    fn get_configuration_metadata(&self) -> Result<capi::AcquirePropertyMetadata> {
+        let mut meta: capi::AcquirePropertyMetadata = Default::default();
+        ApiCall::new("acquire_get_configuration_metadata", ErrorKind::Other).run(|| unsafe {
+            capi::acquire_get_configuration_metadata(self.inner.as_ptr(), &mut meta)
+        })?;
+        Ok(meta)
+    }
+
+    fn stream_stats(&self, stream_id: u32) -> StreamStats {
+        self.stats
+            .lock()
+            .get(&stream_id)
+            .map(StreamCounters::stats)
+            .unwrap_or_default()
+    }
+
+    fn frame_id_anomalies(&self, stream_id: u32) -> Vec<FrameIdAnomaly> {
+        self.stats
+            .lock()
+            .get(&stream_id)
+            .map(StreamCounters::anomalies)
+            .unwrap_or_default()
+    }
+
+    /// Blocks until the runtime reports `state`, or until `timeout` expires.
+    fn wait_for_state(&self, state: DeviceState, timeout: Option<Duration>) -> Result<()> {
+        clear_errors();
+        let start = Instant::now();
+        loop {
+            if self.get_state()? == state {
+                return Ok(());
+            }
+            if let Some(timeout) = timeout {
+                if start.elapsed() >= timeout {
+                    let reason = format!(
+                        "did not report {:?} within {} ms",
+                        state,
+                        timeout.as_millis()
+                    );
+                    return Err(ApiCall::new("acquire_get_state", ErrorKind::Timeout)
+                        .error_with_reason(&reason, None)
+                        .into());
+                }
+            }
+            std::thread::sleep(WAIT_POLL_INTERVAL);
+        }
+    }
+
     fn map_read(&self, stream_id: u32) -> Result<(*mut capi::VideoFrame, *mut capi::VideoFrame)> {
         let mut beg = null_mut();
         let mut end = null_mut();
-        unsafe {
-            capi::acquire_map_read(self.inner.as_ptr(), stream_id, &mut beg, &mut end).ok()?;
-        }
+        ApiCall::new("acquire_map_read", ErrorKind::Other)
+            .stream(stream_id)
+            .run(|| unsafe {
+                capi::acquire_map_read(self.inner.as_ptr(), stream_id, &mut beg, &mut end)
+            })?;
         Ok((beg, end))
     }
 
     fn unmap_read(&self, stream_id: u32, consumed_bytes: usize) -> Result<()> {
-        unsafe {
-            capi::acquire_unmap_read(self.inner.as_ptr(), stream_id, consumed_bytes).ok()?;
-        }
+        ApiCall::new("acquire_unmap_read", ErrorKind::Other)
+            .stream(stream_id)
+            .run(|| unsafe {
+                capi::acquire_unmap_read(self.inner.as_ptr(), stream_id, consumed_bytes)
+            })?;
         Ok(())
     }
+
+    /// Records that the stream's region is no longer mapped, for a map that
+    /// failed or found no data.
+    fn mark_unmapped(&self, stream_id: u32) {
+        self.read_states
+            .lock()
+            .entry(stream_id)
+            .or_default()
+            .is_mapped = false;
+    }
+
+    /// Unmaps the stream's region, of which `consumed_bytes` past the held
+    /// bytes were consumed.
+    ///
+    /// Consumed bytes are only released to the core once no frames of the
+    /// stream are borrowed. Until then they're held. `borrowed` says whether
+    /// frames of this region are still borrowed.
+    fn unmap_region(&self, stream_id: u32, consumed_bytes: usize, borrowed: bool) -> Result<()> {
+        let mut states = self.read_states.lock();
+        let state = states.entry(stream_id).or_default();
+        state.is_mapped = false;
+        if borrowed {
+            state.borrowed_regions += 1;
+        }
+        let released = if state.borrowed_regions == 0 {
+            std::mem::take(&mut state.held_bytes) + consumed_bytes
+        } else {
+            state.held_bytes += consumed_bytes;
+            0
+        };
+        self.unmap_read(stream_id, released)
+    }
+
+    /// Records that the frames of a region unmapped while borrowed are no
+    /// longer borrowed, releasing the held bytes once no others are.
+    fn return_borrowed(&self, stream_id: u32) -> Result<()> {
+        let mut states = self.read_states.lock();
+        let state = states.entry(stream_id).or_default();
+        state.borrowed_regions -= 1;
+        // A mapped region releases the held bytes when it's unmapped.
+        if state.borrowed_regions > 0 || state.is_mapped || state.held_bytes == 0 {
+            return Ok(());
+        }
+        self.map_read(stream_id)?;
+        let held_bytes = std::mem::take(&mut state.held_bytes);
+        self.unmap_read(stream_id, held_bytes)
+    }
 }
 
 impl Drop for RawRuntime {
     fn drop(&mut self) {
         debug!("SHUTDOWN Runtime");
-        unsafe {
-            capi::acquire_shutdown(self.inner.as_mut())
-                .ok()
-                .expect("Core runtime shutdown failed.");
-        }
+        ApiCall::new("acquire_shutdown", ErrorKind::Other)
+            .run(|| unsafe { capi::acquire_shutdown(self.inner.as_mut()) })
+            .expect("Core runtime shutdown failed.");
     }
 }
 
@@ -120,6 +319,64 @@ impl AsRef<NonNull<capi::AcquireRuntime>> for RawRuntime {
 /// The core runtime state
 pub struct Runtime {
     inner: Arc<RawRuntime>,
+    logs: Arc<LogBuffer>,
+    state_callbacks: StateCallbacks,
+    frame_callbacks: Mutex<HashMap<u32, FrameCallback>>,
+    /// The errors of frame callbacks that were replaced or removed, kept for
+    /// the next call to `stop()`.
+    retired_frame_callback_errors: Mutex<Vec<(u32, FrameCallbackError)>>,
+}
+
+impl Drop for Runtime {
+    fn drop(&mut self) {
+        for callback in self.frame_callbacks.lock().values() {
+            callback.is_closed.store(true, Ordering::Release);
+        }
+        // Lets the state watcher exit.
+        self.state_callbacks.lock().callbacks.clear();
+    }
+}
+
+impl Runtime {
+    /// Calls `f` to change the runtime's state, then reports the change as
+    /// caused by `cause`.
+    ///
+    /// The new state is `reached`, if given and `f` succeeds. Otherwise it's
+    /// read back from the runtime.
+    fn change_state<F>(
+        &self,
+        py: Python<'_>,
+        cause: StateChangeCause,
+        reached: Option<DeviceState>,
+        f: F,
+    ) -> PyResult<()>
+    where
+        F: Send + FnOnce() -> PyResult<()>,
+    {
+        // An acquisition that finished on its own before the watcher noticed
+        // shouldn't be put down to this call.
+        notify_state_change(
+            py,
+            &self.state_callbacks,
+            None,
+            self.inner.get_state()?,
+            StateChangeCause::Finished,
+        );
+
+        self.state_callbacks.lock().pending = Some(cause);
+        let result = Python::allow_threads(py, f);
+        let state = match reached {
+            Some(state) if result.is_ok() => Ok(state),
+            _ => self.inner.get_state(),
+        };
+        if let Ok(state) = state {
+            notify_state_change(py, &self.state_callbacks, None, state, cause);
+        }
+        self.state_callbacks.lock().pending = None;
+        result?;
+        state?;
+        Ok(())
+    }
 }
 
 impl AsRef<NonNull<capi::AcquireRuntime>> for Runtime {
@@ -132,83 +389,515 @@ impl AsRef<NonNull<capi::AcquireRuntime>> for Runtime {
 impl Runtime {
     #[new]
     fn new() -> PyResult<Self> {
+        // Register the log buffer first so messages from initialization are
+        // captured.
+        let logs = LogBuffer::register();
         Ok(Self {
             inner: Arc::new(RawRuntime::new()?),
+            logs,
+            state_callbacks: Default::default(),
+            frame_callbacks: Default::default(),
+            retired_frame_callback_errors: Default::default(),
         })
     }
 
     fn start(&self, py: Python<'_>) -> PyResult<()> {
-        Python::allow_threads(py, || Ok(self.inner.start()?))
+        *self.inner.clock_correlation.lock() = Some(ClockCorrelation::sample(py)?);
+        // The runtime may already have finished a short acquisition by the
+        // time `start` returns, so it's reported as running regardless.
+        self.change_state(
+            py,
+            StateChangeCause::Start,
+            Some(DeviceState::Running),
+            || Ok(self.inner.start()?),
+        )
     }
 
     fn stop(&self, py: Python<'_>) -> PyResult<()> {
-        Python::allow_threads(py, || Ok(self.inner.stop()?))
+        self.change_state(py, StateChangeCause::Stop, None, || {
+            Ok(self.inner.stop()?)
+        })?;
+
+        // Let the frame callbacks finish with the frames that were acquired
+        // before the runtime stopped. A callback that called `stop()` can't
+        // finish until it returns, so it isn't waited for.
+        let current = std::thread::current().id();
+        let mut callbacks: Vec<_> = self
+            .frame_callbacks
+            .lock()
+            .iter()
+            .map(|(&stream_id, callback)| {
+                (
+                    stream_id,
+                    callback.drained.clone(),
+                    callback.is_closed.clone(),
+                    callback.error.clone(),
+                    callback.handle.thread().id() == current,
+                )
+            })
+            .collect();
+        callbacks.sort_by_key(|(stream_id, ..)| *stream_id);
+        Python::allow_threads(py, || {
+            let deadline = Instant::now() + FRAME_CALLBACK_DRAIN_TIMEOUT;
+            for (stream_id, drained, is_closed, _, is_current) in &callbacks {
+                if *is_current {
+                    continue;
+                }
+                let epoch = drained.load(Ordering::Acquire);
+                while drained.load(Ordering::Acquire) == epoch && !is_closed.load(Ordering::Acquire)
+                {
+                    if Instant::now() >= deadline {
+                        error!(
+                            "[stream {}] Frame callback still busy {:?} after stopping",
+                            stream_id, FRAME_CALLBACK_DRAIN_TIMEOUT
+                        );
+                        break;
+                    }
+                    std::thread::sleep(WAIT_POLL_INTERVAL);
+                }
+            }
+        });
+
+        // Failed streams stay paused until their errors are raised here.
+        // Retired callbacks whose workers are still running keep their slot,
+        // in case they fail later.
+        let mut errors = Vec::new();
+        self.retired_frame_callback_errors
+            .lock()
+            .retain(|(stream_id, error)| {
+                if let Some(e) = error.lock().take() {
+                    errors.push((*stream_id, e));
+                }
+                Arc::strong_count(error) > 1
+            });
+        errors.extend(
+            callbacks
+                .iter()
+                .filter_map(|(stream_id, _, _, error, _)| Some((*stream_id, error.lock().take()?))),
+        );
+        errors.sort_by_key(|(stream_id, _)| *stream_id);
+        raise_frame_callback_errors(py, errors)
     }
 
     fn abort(&self, py: Python<'_>) -> PyResult<()> {
-        Python::allow_threads(py, || Ok(self.inner.abort()?))
+        self.change_state(py, StateChangeCause::Abort, None, || {
+            Ok(self.inner.abort()?)
+        })
     }
 
-    fn device_manager(&self) -> PyResult<device_manager::DeviceManager> {
+    pub(crate) fn device_manager(&self) -> PyResult<device_manager::DeviceManager> {
         Ok(device_manager::DeviceManager {
             _runtime: self.inner.clone(),
-            inner: NonNull::new(unsafe {
-                capi::acquire_device_manager(self.as_ref().as_ptr()) as _
-            })
-            .ok_or(anyhow!("Failed to get device manager"))?,
+            inner: self.inner.device_manager()?,
         })
     }
 
-    fn set_configuration(&self, properties: &Properties, py: Python<'_>) -> PyResult<Properties> {
+    /// Applies `properties` and returns the configuration the devices
+    /// actually applied.
+    ///
+    /// With `report`, returns `(applied, changes)`, where `changes` lists the
+    /// settings the devices adjusted. With `strict`, raises
+    /// `ConfigurationError` if any setting was adjusted. The adjusted
+    /// configuration has already been applied by then.
+    #[pyo3(signature = (properties, report=false, strict=false))]
+    fn set_configuration(
+        &self,
+        properties: &Properties,
+        report: bool,
+        strict: bool,
+        py: Python<'_>,
+    ) -> PyResult<PyObject> {
         let mut props: capi::AcquireProperties = properties.try_into()?;
-        Python::allow_threads(py, || {
-            unsafe { capi::acquire_configure(self.as_ref().as_ptr(), &mut props) }.ok()
+        let mut requested: Option<Properties> = None;
+        self.change_state(py, StateChangeCause::Configure, None, || {
+            // Identifiers may come from a saved configuration, so their ids
+            // are looked up again rather than trusted.
+            unsafe { device_manager::resolve_all(self.inner.device_manager()?, &mut props) }?;
+            // Resolving a pattern to a device isn't an adjustment, so the
+            // report compares against the resolved request.
+            if report || strict {
+                requested = Some((&props).try_into()?);
+            }
+            ApiCall::new("acquire_configure", ErrorKind::Configuration)
+                .run(|| unsafe { capi::acquire_configure(self.as_ref().as_ptr(), &mut props) })?;
+            self.inner.frame_pool.clear();
+            Ok(())
         })?;
-        Ok((&props).try_into()?)
+        let applied: Properties = (&props).try_into()?;
+        if !report && !strict {
+            return Ok(applied.into_py(py));
+        }
+
+        let requested = requested.expect("resolved before acquire_configure");
+        let changes = config_diff::diff(py, &requested, &applied)?;
+        if strict && !changes.is_empty() {
+            let lines = changes
+                .iter()
+                .map(|change| change.describe(py))
+                .collect::<PyResult<Vec<_>>>()?;
+            // The adjusted configuration is already applied. It's left in
+            // place, since the one before may not be valid anymore either.
+            let err = ApiCall::new("acquire_configure", ErrorKind::Configuration)
+                .error_with_reason(
+                    &format!(
+                        "adjusted {} setting(s), and the adjusted configuration was applied:\n  {}",
+                        changes.len(),
+                        lines.join("\n  ")
+                    ),
+                    None,
+                );
+            err.value(py).setattr("changes", changes.into_py(py))?;
+            return Err(err);
+        }
+        if report {
+            Ok((applied, changes).into_py(py))
+        } else {
+            Ok(applied.into_py(py))
+        }
+    }
+
+    /// Applies `patch` on top of the current configuration with a single
+    /// call to `acquire_configure`.
+    ///
+    /// `patch` maps paths like `video[0].camera.settings.exposure_time_us`
+    /// to values. See `Properties.set_path()`. `report` and `strict` are as
+    /// for `set_configuration()`.
+    #[pyo3(signature = (patch, report=false, strict=false))]
+    fn update_configuration(
+        &self,
+        patch: &PyDict,
+        report: bool,
+        strict: bool,
+        py: Python<'_>,
+    ) -> PyResult<PyObject> {
+        let properties = PyCell::new(py, self.get_configuration(py)?)?;
+        for (path, value) in patch {
+            config_path::set(properties, path.extract()?, value)?;
+        }
+        let properties = properties.borrow();
+        self.set_configuration(&properties, report, strict, py)
+    }
+
+    /// Messages reported by the core runtime, oldest first.
+    ///
+    /// Only the most recent messages are kept. When `since` is given, only
+    /// messages with a later timestamp are returned.
+    #[pyo3(signature = (since=None))]
+    fn get_log_records(&self, since: Option<f64>) -> Vec<LogRecord> {
+        self.logs.records(since)
+    }
+
+    fn clear_log_records(&self) {
+        self.logs.clear()
     }
 
     fn get_configuration(&self, py: Python<'_>) -> PyResult<Properties> {
         let mut props: capi::AcquireProperties = Default::default();
         Python::allow_threads(py, || {
-            unsafe { capi::acquire_get_configuration(self.as_ref().as_ptr(), &mut props) }.ok()
+            ApiCall::new("acquire_get_configuration", ErrorKind::Other).run(|| unsafe {
+                capi::acquire_get_configuration(self.as_ref().as_ptr(), &mut props)
+            })
         })?;
         Ok((&props).try_into()?)
     }
 
     fn get_capabilities(&self, py: Python<'_>) -> PyResult<Capabilities> {
-        let mut meta: capi::AcquirePropertyMetadata = Default::default();
-        Python::allow_threads(py, || {
-            unsafe { capi::acquire_get_configuration_metadata(self.as_ref().as_ptr(), &mut meta) }
-                .ok()
-        })?;
+        let meta = Python::allow_threads(py, || self.inner.get_configuration_metadata())?;
         Ok((&meta).try_into()?)
     }
 
+    /// Checks `properties` against the capabilities of the currently
+    /// configured devices, without applying them.
+    ///
+    /// Only the configured devices can be validated: the core reports
+    /// capabilities only for those, and configuring the requested devices to
+    /// get theirs would open them and replace the configuration. Other
+    /// devices are reported as `DeviceNotConfigured`, unchecked.
+    ///
+    /// Returns the settings the devices can't honor. An empty list means no
+    /// problems were found.
+    fn validate_configuration(
+        &self,
+        properties: &Properties,
+        py: Python<'_>,
+    ) -> PyResult<Vec<ConfigurationViolation>> {
+        let mut requested: capi::AcquireProperties = properties.try_into()?;
+        let mut configured: capi::AcquireProperties = Default::default();
+        let meta = Python::allow_threads(py, || -> PyResult<_> {
+            unsafe { device_manager::resolve_all(self.inner.device_manager()?, &mut requested) }?;
+            ApiCall::new("acquire_get_configuration", ErrorKind::Other).run(|| unsafe {
+                capi::acquire_get_configuration(self.as_ref().as_ptr(), &mut configured)
+            })?;
+            Ok(self.inner.get_configuration_metadata()?)
+        })?;
+        Ok(validation::validate(
+            py,
+            properties,
+            &requested,
+            &configured,
+            &meta,
+        )?)
+    }
+
     fn get_state(&self, py: Python<'_>) -> PyResult<DeviceState> {
-        Ok(Python::allow_threads(py, || unsafe {
-            capi::acquire_get_state(self.as_ref().as_ptr())
-        })
-        .try_into()?)
+        Ok(Python::allow_threads(py, || self.inner.get_state())?)
+    }
+
+    /// Blocks until the runtime reaches `state`.
+    ///
+    /// Raises `AcquireTimeoutError` if `timeout_ms` is given and expires first.
+    #[pyo3(signature = (state, timeout_ms=None))]
+    fn wait_for_state(
+        &self,
+        state: DeviceState,
+        timeout_ms: Option<u64>,
+        py: Python<'_>,
+    ) -> PyResult<()> {
+        let timeout = timeout_ms.map(Duration::from_millis);
+        Python::allow_threads(py, || Ok(self.inner.wait_for_state(state, timeout)?))
+    }
+
+    /// Registers `callback` to be called as `callback(previous, current)`
+    /// whenever the runtime changes state.
+    ///
+    /// Changes made through this object are reported by the call that made
+    /// them. Changes the runtime makes on its own are found by a background
+    /// thread that polls the runtime.
+    fn on_state_change(&self, callback: PyObject, py: Python<'_>) -> PyResult<()> {
+        if !callback.as_ref(py).is_callable() {
+            return Err(PyTypeError::new_err("callback must be callable"));
+        }
+        let mut watch = self.state_callbacks.lock();
+        if watch.callbacks.is_empty() {
+            watch.state = Some(self.inner.get_state()?);
+        }
+        watch.callbacks.push(callback);
+        if !watch.polling {
+            let runtime = Arc::downgrade(&self.inner);
+            let callbacks = self.state_callbacks.clone();
+            std::thread::Builder::new()
+                .name("acquire-state-watcher".into())
+                .spawn(move || state_watcher(runtime, callbacks))?;
+            watch.polling = true;
+        }
+        Ok(())
+    }
+
+    fn __traverse__(&self, visit: PyVisit<'_>) -> Result<(), PyTraverseError> {
+        // The watcher only holds the lock briefly, and never while waiting
+        // for the GIL, but skipping a collection beats blocking one.
+        if let Some(watch) = self.state_callbacks.try_lock() {
+            for callback in &watch.callbacks {
+                visit.call(callback)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn __clear__(&mut self) {
+        self.state_callbacks.lock().callbacks.clear();
     }
 
     fn execute_trigger(&self, stream_id: u32, py: Python<'_>) -> PyResult<()> {
         Python::allow_threads(py, || Ok(self.inner.execute_trigger(stream_id)?))
     }
 
-    fn get_available_data(&self, stream_id: u32) -> PyResult<AvailableDataContext> {
+    #[pyo3(signature = (stream_id, min_frames=0, timeout_ms=None, peek=false))]
+    fn get_available_data(
+        &self,
+        stream_id: u32,
+        min_frames: usize,
+        timeout_ms: Option<u64>,
+        peek: bool,
+    ) -> PyResult<AvailableDataContext> {
         Ok(AvailableDataContext {
             inner: self.inner.clone(),
             stream_id,
+            min_frames,
+            timeout: timeout_ms.map(Duration::from_millis),
+            peek,
             available_data: Python::with_gil(|py| {
                 Py::new(
                     py,
                     AvailableData {
                         inner: Arc::new(Mutex::new(None)),
+                        wait_status: WaitStatus::default(),
                     },
                 )
             })?,
         })
     }
+
+    fn stream_frames(&self, stream_id: u32) -> PyResult<FrameStream> {
+        Ok(FrameStream::new(self.inner.clone(), stream_id)?)
+    }
+
+    /// Calls `callback` with lists of up to `batch` frames from the stream as
+    /// they are acquired, from a background thread.
+    ///
+    /// Passing `None` removes the stream's callback.
+    #[pyo3(signature = (stream_id, callback, batch=1))]
+    fn set_frame_callback(
+        &self,
+        stream_id: u32,
+        callback: Option<PyObject>,
+        batch: usize,
+        py: Python<'_>,
+    ) -> PyResult<()> {
+        if batch == 0 {
+            return Err(PyValueError::new_err("batch must be at least 1"));
+        }
+        if let Some(callback) = &callback {
+            if !callback.as_ref(py).is_callable() {
+                return Err(PyTypeError::new_err("callback must be callable"));
+            }
+        }
+
+        let previous = self.frame_callbacks.lock().remove(&stream_id);
+        let previous = previous.and_then(|previous| {
+            self.retired_frame_callback_errors
+                .lock()
+                .push((stream_id, previous.error.clone()));
+            previous.close(py)
+        });
+        if let Some(callback) = callback {
+            let callback =
+                FrameCallback::new(self.inner.clone(), stream_id, callback, batch, previous)?;
+            self.frame_callbacks.lock().insert(stream_id, callback);
+        }
+        Ok(())
+    }
+
+    /// Host clocks sampled when the runtime was last started, for converting
+    /// frame timestamps. `None` if the runtime has not been started.
+    fn get_clock_correlation(&self) -> Option<ClockCorrelation> {
+        *self.inner.clock_correlation.lock()
+    }
+
+    fn get_stream_stats(&self, stream_id: u32) -> StreamStats {
+        self.inner.stream_stats(stream_id)
+    }
+
+    /// Frames whose ids did not directly follow the previously read frame's,
+    /// since the runtime was last started.
+    fn get_frame_id_anomalies(&self, stream_id: u32) -> Vec<FrameIdAnomaly> {
+        self.inner.frame_id_anomalies(stream_id)
+    }
+
+    /// When enabled, a warning is logged for each frame id anomaly as frames
+    /// are read.
+    #[pyo3(signature = (enabled=true))]
+    fn set_frame_id_warnings(&self, enabled: bool) {
+        self.inner
+            .warn_on_frame_id_anomalies
+            .store(enabled, Ordering::Relaxed);
+    }
+}
+
+/// How often the state watcher polls the runtime.
+const STATE_POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// What caused a change of the runtime's state.
+#[pyclass]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StateChangeCause {
+    /// `Runtime.set_configuration()` was called.
+    Configure,
+    /// `Runtime.start()` was called.
+    Start,
+    /// `Runtime.stop()` was called.
+    Stop,
+    /// `Runtime.abort()` was called.
+    Abort,
+    /// The runtime changed state on its own, e.g. after acquiring
+    /// `max_frame_count` frames, or after a device failed.
+    Finished,
+}
+
+/// The callbacks registered with `Runtime.on_state_change()`.
+#[derive(Default)]
+struct StateWatch {
+    callbacks: Vec<PyObject>,
+    /// The state the callbacks were last told about. `None` until the first
+    /// callback is registered.
+    state: Option<DeviceState>,
+    /// Whether a thread is polling for changes the runtime makes on its own.
+    polling: bool,
+    /// Set while a call like `Runtime.stop()` changes the state, so the
+    /// watcher leaves reporting the change to that call.
+    pending: Option<StateChangeCause>,
+}
+
+type StateCallbacks = Arc<Mutex<StateWatch>>;
+
+/// Tells the callbacks in `watch` that the state changed to `current`, unless
+/// they already know.
+///
+/// With `expected`, only does so if the callbacks were last told about that
+/// state and no call is changing the state, so a poll that raced with an api
+/// call doesn't report a stale state or misreport its cause.
+fn notify_state_change(
+    py: Python<'_>,
+    watch: &Mutex<StateWatch>,
+    expected: Option<DeviceState>,
+    current: DeviceState,
+    cause: StateChangeCause,
+) {
+    let (previous, callbacks) = {
+        let mut watch = watch.lock();
+        let previous = match watch.state {
+            Some(previous) if previous != current => previous,
+            _ => return,
+        };
+        if expected.map_or(false, |expected| {
+            expected != previous || watch.pending.is_some()
+        }) {
+            return;
+        }
+        watch.state = Some(current);
+        let callbacks: Vec<PyObject> = watch.callbacks.iter().map(|c| c.clone_ref(py)).collect();
+        (previous, callbacks)
+    };
+    for callback in callbacks {
+        if let Err(e) = callback.call1(py, (previous, current, cause)) {
+            e.print(py);
+        }
+    }
+}
+
+/// Polls the runtime's state and tells the callbacks in `watch` about
+/// changes the runtime made on its own.
+///
+/// Exits once the runtime has been dropped or the callbacks are cleared.
+fn state_watcher(runtime: Weak<RawRuntime>, watch: StateCallbacks) {
+    loop {
+        std::thread::sleep(STATE_POLL_INTERVAL);
+        let previous = {
+            let mut watch = watch.lock();
+            if watch.callbacks.is_empty() {
+                watch.polling = false;
+                return;
+            }
+            if watch.pending.is_some() {
+                continue;
+            }
+            watch.state
+        };
+        let current = match runtime.upgrade().map(|runtime| runtime.get_state()) {
+            Some(Ok(state)) => state,
+            Some(Err(e)) => {
+                error!("Failed to poll the runtime state: {}", e);
+                break;
+            }
+            None => break,
+        };
+        if previous != Some(current) {
+            Python::with_gil(|py| {
+                notify_state_change(py, &watch, previous, current, StateChangeCause::Finished)
+            });
+        }
+    }
+    watch.lock().polling = false;
 }
 
 /// References to a region of raw data being read from a video stream.
@@ -222,58 +911,386 @@ struct RawAvailableData {
     /// The video stream owning the region
     stream_id: u32,
 
+    /// Byte offset from `beg` of each frame in the region.
+    offsets: Vec<usize>,
+
     /// When none, the entire region will be unmapped. Otherwise just the first
     /// `consumed_bytes`.
-    consumed_bytes: Option<usize>,
+    consumed_bytes: Mutex<Option<usize>>,
+
+    /// Set once the region was unmapped while its frames were still
+    /// borrowed. They stay readable, since their bytes are held.
+    is_unmapped: AtomicBool,
 }
 
+/// The region a `VideoFrame` reads from, taken when the `AvailableDataContext`
+/// it came from exits.
+///
+/// Arrays that borrow frame data hold on to the region as well, so that its
+/// frames are only released once the last of them is gone. See `retire()`.
+type FrameStore = Arc<Mutex<Option<Arc<RawAvailableData>>>>;
+
 unsafe impl Send for RawAvailableData {}
 unsafe impl Sync for RawAvailableData {}
 
-impl RawAvailableData {
-    fn get_frame_count(&self) -> usize {
-        let mut count = 0;
-        unsafe {
-            let mut cur = self.beg.as_ptr();
-            let end = self.end.as_ptr();
-            while cur < end {
-                let frame: &capi::VideoFrame = &std::ptr::read_unaligned(cur);
-                log::trace!(
-                    "[stream {}] Advancing count for frame {} w size {}",
-                    self.stream_id,
-                    frame.frame_id,
-                    frame.bytes_of_frame
+/// Calls `f` with the header of each frame in the region `[beg, end)`.
+///
+/// Null or empty regions have no frames.
+fn for_each_frame(
+    beg: *mut capi::VideoFrame,
+    end: *mut capi::VideoFrame,
+    mut f: impl FnMut(&capi::VideoFrame),
+) {
+    if beg.is_null() || end.is_null() {
+        return;
+    }
+    unsafe {
+        let mut cur = beg;
+        while cur < end {
+            let frame: &capi::VideoFrame = &std::ptr::read_unaligned(cur);
+            assert!(frame.bytes_of_frame > 0);
+            f(frame);
+            cur = cur.byte_offset(frame.bytes_of_frame as _);
+        }
+    }
+}
+
+/// Returns the byte offset from `beg` of each frame in the region
+/// `[beg, end)`, by walking the frame headers.
+fn index_frames(
+    stream_id: u32,
+    beg: *mut capi::VideoFrame,
+    end: *mut capi::VideoFrame,
+) -> Vec<usize> {
+    let mut offsets = Vec::new();
+    let mut offset = 0;
+    for_each_frame(beg, end, |frame| {
+        log::trace!(
+            "[stream {}] Indexing frame {} w size {} at offset {}",
+            stream_id,
+            frame.frame_id,
+            frame.bytes_of_frame,
+            offset
+        );
+        offsets.push(offset);
+        offset += frame.bytes_of_frame as usize;
+    });
+    offsets
+}
+
+/// The frames found in a mapped region, kept while polling so that mapping
+/// the stream again doesn't walk the frames already seen.
+struct FrameIndex {
+    beg: *mut capi::VideoFrame,
+    end: *mut capi::VideoFrame,
+    offsets: Vec<usize>,
+}
+
+impl FrameIndex {
+    /// Indexes the region `[beg, end)`.
+    ///
+    /// When `previous` indexed the start of the same region, which is the
+    /// case when nothing was consumed since, only the frames past its end are
+    /// walked.
+    fn new(
+        stream_id: u32,
+        beg: *mut capi::VideoFrame,
+        end: *mut capi::VideoFrame,
+        previous: Option<FrameIndex>,
+    ) -> Self {
+        let offsets = match previous {
+            Some(previous) if !beg.is_null() && previous.beg == beg && previous.end <= end => {
+                let base = unsafe { previous.end.byte_offset_from(beg) } as usize;
+                let mut offsets = previous.offsets;
+                offsets.extend(
+                    index_frames(stream_id, previous.end, end)
+                        .into_iter()
+                        .map(|offset| base + offset),
                 );
-                assert!(frame.bytes_of_frame > 0);
-                cur = cur.byte_offset(frame.bytes_of_frame as _);
-                count += 1;
+                offsets
             }
-        }
-        count
+            _ => index_frames(stream_id, beg, end),
+        };
+        Self { beg, end, offsets }
     }
 }
 
-impl Drop for RawAvailableData {
-    fn drop(&mut self) {
+impl RawAvailableData {
+    /// Maps the readable region of the stream.
+    ///
+    /// Returns `None` when no data is available.
+    fn map(runtime: &Arc<RawRuntime>, stream_id: u32) -> Result<Option<Self>> {
+        Self::remap(runtime, stream_id, None)
+    }
+
+    /// Like `map()`, but reuses `previous`, the index of the last region
+    /// mapped from the stream, where it still applies.
+    fn remap(
+        runtime: &Arc<RawRuntime>,
+        stream_id: u32,
+        previous: Option<FrameIndex>,
+    ) -> Result<Option<Self>> {
+        // Another reader still has the previous region mapped. Mapping
+        // again would return the same frames, and waiting wouldn't help,
+        // since the core's queue only fills up meanwhile.
+        let held_bytes = {
+            let mut states = runtime.read_states.lock();
+            let state = states.entry(stream_id).or_default();
+            if state.is_mapped {
+                return Err(ApiCall::new("acquire_map_read", ErrorKind::State)
+                    .stream(stream_id)
+                    .error_with_reason(
+                        "was skipped: the stream is already being read, e.g. by another \
+                         get_available_data() context that hasn't exited",
+                        None,
+                    )
+                    .into());
+            }
+            state.is_mapped = true;
+            state.held_bytes
+        };
+        let (beg, end) = match runtime.map_read(stream_id) {
+            Ok(region) => region,
+            Err(e) => {
+                runtime.mark_unmapped(stream_id);
+                return Err(e);
+            }
+        };
+        if beg.is_null() || end.is_null() || beg == end {
+            runtime.mark_unmapped(stream_id);
+            return Ok(None);
+        }
+        // Skip the frames that were consumed but are still borrowed.
+        let nbytes = unsafe { end.byte_offset_from(beg) } as usize;
+        if held_bytes >= nbytes {
+            runtime.unmap_region(stream_id, 0, false)?;
+            return Ok(None);
+        }
+        let beg = unsafe { beg.byte_add(held_bytes) };
+        let nbytes = nbytes - held_bytes;
+
+        log::trace!(
+            "[stream {}] ACQUIRED {:p}-{:p}:{} bytes",
+            stream_id,
+            beg,
+            end,
+            nbytes
+        );
+        // Polls keep mapping the same region until more frames arrive, and
+        // only new frames are counted.
+        let changed = previous
+            .as_ref()
+            .map_or(true, |previous| (previous.beg, previous.end) != (beg, end));
+        let FrameIndex { offsets, .. } = FrameIndex::new(stream_id, beg, end, previous);
+        if changed {
+            runtime
+                .stats
+                .lock()
+                .entry(stream_id)
+                .or_default()
+                .on_map(offsets.len());
+        }
+
+        Ok(Some(RawAvailableData {
+            runtime: runtime.clone(),
+            beg: NonNull::new(beg).ok_or(anyhow!("Expected non-null buffer"))?,
+            end: NonNull::new(end).ok_or(anyhow!("Expected non-null buffer"))?,
+            stream_id,
+            offsets,
+            consumed_bytes: Mutex::new(None),
+            is_unmapped: AtomicBool::new(false),
+        }))
+    }
+
+    /// Maps the readable region of the stream once it holds at least
+    /// `min_frames` frames, the timeout expires, or the runtime is no longer
+    /// running.
+    fn map_wait(
+        runtime: &Arc<RawRuntime>,
+        stream_id: u32,
+        min_frames: usize,
+        timeout: Option<Duration>,
+    ) -> Result<(Option<Self>, WaitStatus)> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let mut index = None;
+        loop {
+            // Check the state before mapping so that frames produced just
+            // before the runtime stopped are still returned.
+            let is_running = matches!(runtime.get_state()?, DeviceState::Running);
+            let data = Self::remap(runtime, stream_id, index.take())?;
+            let count = data.as_ref().map_or(0, |data| data.get_frame_count());
+
+            let status = if count >= min_frames {
+                Some(WaitStatus::Ready)
+            } else if !is_running {
+                Some(WaitStatus::Stopped)
+            } else if deadline.map_or(false, |deadline| Instant::now() >= deadline) {
+                Some(WaitStatus::TimedOut)
+            } else {
+                None
+            };
+            if let Some(status) = status {
+                return Ok((data, status));
+            }
+
+            // Release the region without consuming anything and poll again.
+            index = data.map(Self::release_keeping_index);
+            std::thread::sleep(WAIT_POLL_INTERVAL);
+        }
+    }
+
+    fn get_frame_count(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// The `index`th frame of the region.
+    ///
+    /// Panics if `index` is out of range.
+    fn frame(&self, index: usize) -> NonNull<capi::VideoFrame> {
+        let offset = self.offsets[index];
+        unsafe { NonNull::new_unchecked(self.beg.as_ptr().byte_add(offset)) }
+    }
+
+    /// Marks the first `n_frames` frames as consumed, so that only they are
+    /// unmapped when the region is dropped.
+    fn consume(&self, n_frames: usize) {
+        let nbytes = unsafe { self.end.as_ptr().byte_offset_from(self.beg.as_ptr()) } as usize;
+        let consumed_bytes = self.offsets.get(n_frames).copied().unwrap_or(nbytes);
+        *self.consumed_bytes.lock() = Some(consumed_bytes);
+    }
+
+    /// Consumes none of the region, so the same frames are returned by the
+    /// next map once it's unmapped.
+    fn release(&self) {
+        *self.consumed_bytes.lock() = Some(0);
+    }
+
+    /// Like `release()`, but returns the region's frame index for the next
+    /// `remap()`.
+    fn release_keeping_index(mut self) -> FrameIndex {
+        self.release();
+        FrameIndex {
+            beg: self.beg.as_ptr(),
+            end: self.end.as_ptr(),
+            offsets: std::mem::take(&mut self.offsets),
+        }
+    }
+
+    /// Unmaps the region while it may still be borrowed, so that the stream
+    /// can be read again.
+    ///
+    /// The frames stay readable until the region is dropped, since their
+    /// bytes are held rather than released to the core. Does nothing if the
+    /// region was already unmapped.
+    fn unmap_borrowed(&self) {
+        if !self.is_unmapped.swap(true, Ordering::AcqRel) {
+            self.unmap(true);
+        }
+    }
+
+    /// Records the consumed frames and unmaps the region.
+    fn unmap(&self, borrowed: bool) {
         let consumed_bytes = self
             .consumed_bytes
+            .lock()
             .unwrap_or(unsafe { self.end.as_ptr().byte_offset_from(self.beg.as_ptr()) } as usize);
         log::debug!(
-            "[stream {}] DROP read region: {:p}-{:p}:{}",
+            "[stream {}] UNMAP read region: {:p}-{:p}:{}",
             self.stream_id,
             self.beg.as_ptr(),
             self.end.as_ptr(),
             consumed_bytes
         );
+
+        let mut frames = Vec::new();
+        for_each_frame(
+            self.beg.as_ptr(),
+            unsafe { self.beg.as_ptr().byte_add(consumed_bytes) },
+            |frame| frames.push((frame.frame_id, frame.timestamps.acq_thread)),
+        );
+        let anomalies = self
+            .runtime
+            .stats
+            .lock()
+            .entry(self.stream_id)
+            .or_default()
+            .on_consume(frames, consumed_bytes);
+        if self
+            .runtime
+            .warn_on_frame_id_anomalies
+            .load(Ordering::Relaxed)
+        {
+            for anomaly in anomalies {
+                log::warn!(
+                    "[stream {}] Frame id anomaly: {:?}",
+                    self.stream_id,
+                    anomaly
+                );
+            }
+        }
+
         self.runtime
-            .unmap_read(self.stream_id, consumed_bytes)
+            .unmap_region(self.stream_id, consumed_bytes, borrowed)
             .expect("Unexpected failure: Was the runtime NULL?");
     }
 }
 
+impl Drop for RawAvailableData {
+    fn drop(&mut self) {
+        if !self.is_unmapped.load(Ordering::Acquire) {
+            self.unmap(false);
+        } else if let Err(e) = self.runtime.return_borrowed(self.stream_id) {
+            error!(
+                "[stream {}] Failed to release borrowed frames: {}",
+                self.stream_id, e
+            );
+        }
+    }
+}
+
+/// Unmaps a region its reader is done with.
+///
+/// If frames of the region are still borrowed, e.g. by an array from
+/// `VideoFrame.data(copy=False)`, the region is unmapped anyway so that the
+/// stream can be read past them, but they aren't released to the core until
+/// the last borrower is gone.
+fn retire(data: Option<Arc<RawAvailableData>>) {
+    if let Some(data) = data {
+        if let Err(data) = Arc::try_unwrap(data) {
+            data.unmap_borrowed();
+        }
+    }
+}
+
+/// Why a call to `Runtime.get_available_data()` stopped waiting for frames.
+#[pyclass]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WaitStatus {
+    /// At least the requested number of frames were available.
+    Ready,
+    /// The timeout expired before enough frames were available.
+    TimedOut,
+    /// The runtime left the `Running` state before enough frames were
+    /// available.
+    Stopped,
+}
+
+impl Default for WaitStatus {
+    fn default() -> Self {
+        WaitStatus::Ready
+    }
+}
+
+/// How long to sleep between polls of the video queue while waiting for
+/// frames.
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
 #[pyclass]
 pub(crate) struct AvailableData {
-    inner: Arc<Mutex<Option<RawAvailableData>>>,
+    inner: FrameStore,
+
+    #[pyo3(get)]
+    wait_status: WaitStatus,
 }
 
 #[pymethods]
@@ -286,6 +1303,31 @@ impl AvailableData {
         }
     }
 
+    /// Marks the first `n_frames` frames as consumed.
+    ///
+    /// Only consumed frames are released when the context exits. The rest are
+    /// returned again by the next `get_available_data()`.
+    fn consume(&self, n_frames: usize) -> PyResult<()> {
+        match &*self.inner.lock() {
+            Some(inner) => {
+                let count = inner.get_frame_count();
+                if n_frames > count {
+                    return Err(PyValueError::new_err(format!(
+                        "Cannot consume {} frames, only {} are available",
+                        n_frames, count
+                    )));
+                }
+                inner.consume(n_frames);
+                Ok(())
+            }
+            None if n_frames == 0 => Ok(()),
+            None => Err(PyValueError::new_err(format!(
+                "Cannot consume {} frames, none are available",
+                n_frames
+            ))),
+        }
+    }
+
     fn frames(&self) -> VideoFrameIterator {
         VideoFrameIterator {
             inner: if let Some(frames) = &*self.inner.lock() {
@@ -303,82 +1345,684 @@ impl AvailableData {
     fn __iter__(slf: PyRef<'_, Self>) -> PyResult<Py<VideoFrameIterator>> {
         Py::new(slf.py(), slf.frames())
     }
-}
 
-impl AvailableData {
-    fn invalidate(&mut self) {
-        // Will drop the RawAvailableData and cause Available data to act like
-        // an empty iterator.
-        *self.inner.lock() = None;
+    fn __len__(&self) -> usize {
+        self.get_frame_count()
     }
-}
 
-#[pyclass]
+    /// Returns the frame at an index, or a list of the frames in a slice,
+    /// without walking the region.
+    fn __getitem__(&self, index: &PyAny) -> PyResult<PyObject> {
+        let py = index.py();
+        let inner = self.inner.lock();
+        let count = inner.as_ref().map_or(0, |data| data.get_frame_count());
+        let frame = |i: usize| VideoFrame {
+            _store: self.inner.clone(),
+            cur: inner.as_ref().expect("index is in range").frame(i),
+        };
+
+        if let Ok(slice) = index.downcast::<PySlice>() {
+            let indices = slice.indices(count as _)?;
+            let frames: Vec<VideoFrame> = (0..indices.slicelength)
+                .map(|k| frame((indices.start + k as isize * indices.step) as usize))
+                .collect();
+            return Ok(frames.into_py(py));
+        }
+
+        let i: isize = index.extract()?;
+        let resolved = if i < 0 { i + count as isize } else { i };
+        if resolved < 0 || resolved as usize >= count {
+            return Err(PyIndexError::new_err(format!(
+                "frame index {} out of range for {} frames",
+                i, count
+            )));
+        }
+        Ok(frame(resolved as usize).into_py(py))
+    }
+
+    /// Copies every frame into one `(N, planes, height, width, channels)`
+    /// array.
+    ///
+    /// All frames must have the same shape and sample type. An empty region
+    /// gives an empty `uint8` array.
+    fn to_array(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        // Pinned like in `detach_all`.
+        let Some(data) = self.inner.lock().clone() else {
+            return Ok(PyArray5::<u8>::zeros(py, [0; 5], false).to_object(py));
+        };
+        let data = FrameDataGuard { data: Some(data) };
+        let frames: Vec<VideoFrame> = self.frames().collect();
+        let layouts = frames
+            .iter()
+            .map(VideoFrame::layout)
+            .collect::<Result<Vec<_>>>()?;
+        if frames.is_empty() {
+            return Ok(PyArray5::<u8>::zeros(py, [0; 5], false).to_object(py));
+        }
+
+        let first = &layouts[0];
+        for layout in &layouts {
+            if layout.is_packed {
+                return Err(PyValueError::new_err(
+                    "Frames with packed samples can't be stacked. Use VideoFrame.data() instead.",
+                ));
+            }
+            if layout.dims != first.dims || layout.sample_type != first.sample_type {
+                return Err(PyValueError::new_err(
+                    "Frames in the region have different shapes or sample types",
+                ));
+            }
+        }
+        let frame_dims = first.dims;
+        let (planes, height, width, channels) = frame_dims.into_pattern();
+        let dims = [frames.len(), planes, height, width, channels];
+        let sources: Vec<(usize, Ix4)> = frames
+            .iter()
+            .zip(&layouts)
+            .map(|(f, l)| (unsafe { f.cur.as_ref().data.as_ptr() } as usize, l.strides))
+            .collect();
+
+        macro_rules! stack {
+            ($T:ty) => {{
+                let array = unsafe { PyArray5::<$T>::new(py, dims, false) };
+                let dst = array.data() as usize;
+                let frame_len = frame_dims.size();
+                Python::allow_threads(py, || {
+                    for (i, (src, strides)) in sources.iter().enumerate() {
+                        unsafe {
+                            copy_strided(
+                                *src as *const $T,
+                                frame_dims,
+                                *strides,
+                                (dst as *mut $T).add(i * frame_len),
+                            );
+                        }
+                    }
+                });
+                array.to_object(py)
+            }};
+        }
+        let array = match first.sample_type {
+            SampleType::U8 => stack!(u8),
+            SampleType::U16 | SampleType::U10 | SampleType::U12 | SampleType::U14 => stack!(u16),
+            SampleType::I8 => stack!(i8),
+            SampleType::I16 => stack!(i16),
+            SampleType::F32 => stack!(f32),
+        };
+        drop(data);
+        Ok(array)
+    }
+
+    /// Copies every frame into buffers owned by the returned frames, which
+    /// stay valid after the context exits.
+    fn detach_all(&self, py: Python<'_>) -> Vec<OwnedVideoFrame> {
+        // Clone the region so it stays mapped without holding the lock while
+        // the GIL is released, which would block threads that take the lock
+        // while holding the GIL.
+        let data = FrameDataGuard {
+            data: self.inner.lock().clone(),
+        };
+        Python::allow_threads(py, || {
+            let Some(region) = data.data.as_deref() else {
+                return Vec::new();
+            };
+            (0..region.get_frame_count())
+                .map(|i| OwnedVideoFrame::copy(region, region.frame(i).as_ptr()))
+                .collect()
+        })
+    }
+
+    /// Returns the frame ids and timestamps of the frames as a dict of
+    /// parallel arrays.
+    fn metadata_table<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        // Pinned like in `detach_all`.
+        let data = FrameDataGuard {
+            data: self.inner.lock().clone(),
+        };
+        let (frame_ids, hardware, acq_thread) = Python::allow_threads(py, || {
+            let mut frame_ids = Vec::new();
+            let mut hardware = Vec::new();
+            let mut acq_thread = Vec::new();
+            if let Some(region) = data.data.as_deref() {
+                for i in 0..region.get_frame_count() {
+                    let header = unsafe { region.frame(i).as_ref() };
+                    frame_ids.push(header.frame_id);
+                    hardware.push(header.timestamps.hardware);
+                    acq_thread.push(header.timestamps.acq_thread);
+                }
+            }
+            (frame_ids, hardware, acq_thread)
+        });
+        let table = PyDict::new(py);
+        table.set_item("frame_id", PyArray1::from_vec(py, frame_ids))?;
+        table.set_item("hardware", PyArray1::from_vec(py, hardware))?;
+        table.set_item("acq_thread", PyArray1::from_vec(py, acq_thread))?;
+        Ok(table)
+    }
+}
+
+impl AvailableData {
+    /// Takes the region, so that `AvailableData` acts like an empty iterator.
+    ///
+    /// The region is unmapped when the returned value is retired.
+    fn invalidate(&mut self) -> Option<Arc<RawAvailableData>> {
+        self.inner.lock().take()
+    }
+}
+
+#[pyclass]
 pub(crate) struct AvailableDataContext {
     inner: Arc<RawRuntime>,
     stream_id: u32,
+    /// Wait until at least this many frames are available.
+    min_frames: usize,
+    /// Give up waiting after this long. When `None`, wait until the runtime
+    /// stops running.
+    timeout: Option<Duration>,
+    /// Leave the frames queued on exit unless they were explicitly consumed.
+    peek: bool,
     available_data: Py<AvailableData>,
 }
 
 #[pymethods]
 impl AvailableDataContext {
-    fn __enter__(&mut self) -> PyResult<Py<AvailableData>> {
-        let AvailableDataContext {
-            inner,
-            stream_id,
-            available_data,
-        } = self;
-        let stream_id = *stream_id;
-        let (beg, end) = inner.map_read(stream_id)?;
-        let nbytes = if beg.is_null() || end.is_null() {
-            0
-        } else {
-            unsafe { end.byte_offset_from(beg) }
-        };
-
-        log::trace!(
-            "[stream {}] ACQUIRED {:p}-{:p}:{} bytes",
-            stream_id,
-            beg,
-            end,
-            nbytes
-        );
-        *available_data = Python::with_gil(|py| {
-            Py::new(
-                py,
-                AvailableData {
-                    inner: Arc::new(Mutex::new(if nbytes > 0 {
-                        Some(RawAvailableData {
-                            runtime: self.inner.clone(),
-                            beg: NonNull::new(beg).ok_or(anyhow!("Expected non-null buffer"))?,
-                            end: NonNull::new(end).ok_or(anyhow!("Expected non-null buffer"))?,
-                            stream_id,
-                            consumed_bytes: None,
-                        })
-                    } else {
-                        None
-                    })),
-                },
-            )
+    fn __enter__(&mut self, py: Python<'_>) -> PyResult<Py<AvailableData>> {
+        let (data, wait_status) = Python::allow_threads(py, || {
+            if self.min_frames > 0 {
+                RawAvailableData::map_wait(
+                    &self.inner,
+                    self.stream_id,
+                    self.min_frames,
+                    self.timeout,
+                )
+            } else {
+                Ok((
+                    RawAvailableData::map(&self.inner, self.stream_id)?,
+                    WaitStatus::Ready,
+                ))
+            }
         })?;
+        let data = data.map(|data| {
+            if self.peek {
+                data.consume(0);
+            }
+            Arc::new(data)
+        });
+        self.available_data = Py::new(
+            py,
+            AvailableData {
+                inner: Arc::new(Mutex::new(data)),
+                wait_status,
+            },
+        )?;
         return Ok(self.available_data.clone());
     }
 
-    fn __exit__(&mut self, _exc_type: &PyAny, _exc_value: &PyAny, _traceback: &PyAny) {
+    fn __exit__(
+        &mut self,
+        _exc_type: &PyAny,
+        _exc_value: &PyAny,
+        _traceback: &PyAny,
+        py: Python<'_>,
+    ) {
+        let data = self.available_data.as_ref(py).borrow_mut().invalidate();
+        Python::allow_threads(py, move || retire(data));
+    }
+}
+
+/// How long the `FrameStream` worker waits for data before checking whether
+/// the stream was closed.
+const FRAME_STREAM_POLL_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// How long the `FrameStream` worker sleeps between polls while waiting for
+/// the runtime to start.
+const FRAME_STREAM_IDLE_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A request from `FrameStream.__anext__()` to resolve `future` with the next
+/// frame once one is available.
+struct FrameRequest {
+    event_loop: PyObject,
+    future: PyObject,
+}
+
+/// Frames from the most recently mapped region that haven't been yielded yet.
+type PendingFrames = Arc<Mutex<Option<VideoFrameIteratorInner>>>;
+
+/// Asynchronous iterator over the frames of a video stream.
+///
+/// A worker thread waits for data and wakes the asyncio event loop only once
+/// new frames have been mapped. Frames are only valid until the next frame is
+/// requested. The region is unmapped once its last frame has been yielded, so
+/// other readers aren't held up in between.
+#[pyclass]
+pub(crate) struct FrameStream {
+    pending: PendingFrames,
+    requests: mpsc::Sender<FrameRequest>,
+    /// Set once the runtime stopped and all data was yielded.
+    is_done: Arc<AtomicBool>,
+    /// Set when the stream is dropped so that the worker exits.
+    is_closed: Arc<AtomicBool>,
+}
+
+impl FrameStream {
+    fn new(runtime: Arc<RawRuntime>, stream_id: u32) -> Result<Self> {
+        // Streams created before the runtime starts wait for it, rather than
+        // ending because it isn't running.
+        let start = Start {
+            count: runtime.starts.load(Ordering::Acquire),
+            is_running: matches!(runtime.get_state()?, DeviceState::Running),
+        };
+        let (requests, receiver) = mpsc::channel();
+        let pending = PendingFrames::default();
+        let is_done = Arc::new(AtomicBool::new(false));
+        let is_closed = Arc::new(AtomicBool::new(false));
+        {
+            let pending = pending.clone();
+            let is_done = is_done.clone();
+            let is_closed = is_closed.clone();
+            std::thread::Builder::new()
+                .name(format!("acquire-stream-{}", stream_id))
+                .spawn(move || {
+                    frame_stream_worker(
+                        runtime, stream_id, start, receiver, pending, is_done, is_closed,
+                    )
+                })?;
+        }
+        Ok(Self {
+            pending,
+            requests,
+            is_done,
+            is_closed,
+        })
+    }
+
+    /// Takes the region backing the frames that have been yielded so far.
+    ///
+    /// The region is unmapped when the returned value is retired.
+    fn invalidate(&self) -> Option<Arc<RawAvailableData>> {
+        let frames = self.pending.lock().take()?;
+        let data = frames.store.lock().take();
+        data
+    }
+}
+
+impl Drop for FrameStream {
+    fn drop(&mut self) {
+        self.is_closed.store(true, Ordering::Release);
+        // Unmapping the region waits on the runtime.
+        let data = self.invalidate();
+        Python::with_gil(|py| py.allow_threads(move || retire(data)));
+    }
+}
+
+#[pymethods]
+impl FrameStream {
+    fn __aiter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __anext__(&mut self, py: Python<'_>) -> PyResult<Option<PyObject>> {
+        let event_loop = py.import("asyncio")?.call_method0("get_running_loop")?;
+        let future = event_loop.call_method0("create_future")?;
+
+        let next = self
+            .pending
+            .lock()
+            .as_mut()
+            .and_then(|frames| frames.next());
+        if let Some(frame) = next {
+            future.call_method1("set_result", (frame.into_py(py),))?;
+            let exhausted = self.pending.lock().as_ref().and_then(|f| f.exhausted());
+            if let Some(data) = exhausted {
+                Python::allow_threads(py, move || data.unmap_borrowed());
+            }
+            return Ok(Some(future.to_object(py)));
+        }
+
+        let data = self.invalidate();
+        Python::allow_threads(py, move || retire(data));
+        if self.is_done.load(Ordering::Acquire) {
+            return Ok(None);
+        }
+        self.requests
+            .send(FrameRequest {
+                event_loop: event_loop.to_object(py),
+                future: future.to_object(py),
+            })
+            .map_err(|_| anyhow!("Frame stream worker exited unexpectedly"))?;
+        Ok(Some(future.to_object(py)))
+    }
+}
+
+/// Whether the runtime was running when a `FrameStream` was created, and how
+/// many times it had been started.
+#[derive(Debug, Clone, Copy)]
+struct Start {
+    count: u64,
+    is_running: bool,
+}
+
+/// Waits for data on behalf of a `FrameStream`, resolving one request at a
+/// time, until the stream is dropped.
+///
+/// The stream ends once the runtime isn't running and no frames are left. If
+/// the runtime wasn't running when the stream was created, that only counts
+/// once it has been started since, or once frames have been yielded.
+fn frame_stream_worker(
+    runtime: Arc<RawRuntime>,
+    stream_id: u32,
+    start: Start,
+    requests: mpsc::Receiver<FrameRequest>,
+    pending: PendingFrames,
+    is_done: Arc<AtomicBool>,
+    is_closed: Arc<AtomicBool>,
+) {
+    let mut has_started = start.is_running;
+    while let Ok(request) = requests.recv() {
+        let result = loop {
+            if is_closed.load(Ordering::Acquire) {
+                return;
+            }
+            has_started |= runtime.starts.load(Ordering::Acquire) != start.count;
+            match RawAvailableData::map_wait(
+                &runtime,
+                stream_id,
+                1,
+                Some(FRAME_STREAM_POLL_TIMEOUT),
+            ) {
+                Ok((data, WaitStatus::TimedOut)) => {
+                    if let Some(data) = data {
+                        data.release();
+                    }
+                }
+                Ok((None, WaitStatus::Stopped)) if !has_started => {
+                    std::thread::sleep(FRAME_STREAM_IDLE_INTERVAL);
+                }
+                Ok((data, _)) => {
+                    has_started |= data.is_some();
+                    break Ok(data);
+                }
+                Err(e) => break Err(e),
+            }
+        };
         Python::with_gil(|py| {
-            (&self.available_data).as_ref(py).borrow_mut().invalidate();
+            if let Err(e) = resolve_frame_request(py, request, result, &pending, &is_done) {
+                error!(
+                    "[stream {}] Failed to resolve frame request: {}",
+                    stream_id, e
+                );
+            }
         });
     }
 }
 
+/// Schedules `request.future` to be resolved on its event loop with the first
+/// frame of `result`, or with `StopAsyncIteration` once the stream has ended.
+fn resolve_frame_request(
+    py: Python<'_>,
+    request: FrameRequest,
+    result: Result<Option<RawAvailableData>>,
+    pending: &PendingFrames,
+    is_done: &AtomicBool,
+) -> PyResult<()> {
+    let (method, value) = match result {
+        Ok(Some(data)) => {
+            let (beg, end) = (data.beg, data.end);
+            let mut frames = VideoFrameIteratorInner {
+                store: Arc::new(Mutex::new(Some(Arc::new(data)))),
+                cur: Mutex::new(beg),
+                end,
+            };
+            let frame = frames.next();
+            *pending.lock() = Some(frames);
+            ("set_result", frame.into_py(py))
+        }
+        Ok(None) => {
+            is_done.store(true, Ordering::Release);
+            (
+                "set_exception",
+                PyStopAsyncIteration::new_err(()).into_value(py).into_py(py),
+            )
+        }
+        Err(e) => ("set_exception", PyErr::from(e).into_value(py).into_py(py)),
+    };
+
+    let pending = pending.clone();
+    let callback = PyCFunction::new_closure(py, None, None, move |args, _kwargs| {
+        let (future, value): (&PyAny, &PyAny) = args.extract()?;
+        if future.call_method0("done")?.is_true()? {
+            // The awaiting task was cancelled. Leave the frames queued for
+            // the next map.
+            if let Some(frames) = pending.lock().take() {
+                if let Some(data) = frames.store.lock().take() {
+                    data.release();
+                    retire(Some(data));
+                }
+            }
+            return PyResult::Ok(());
+        }
+        future.call_method1(method, (value,))?;
+        let exhausted = pending.lock().as_ref().and_then(|f| f.exhausted());
+        if let Some(data) = exhausted {
+            future.py().allow_threads(move || data.unmap_borrowed());
+        }
+        Ok(())
+    })?;
+    request.event_loop.call_method1(
+        py,
+        "call_soon_threadsafe",
+        (callback, request.future, value),
+    )?;
+    Ok(())
+}
+
+/// How long a frame callback worker sleeps between polls while the runtime is
+/// not running.
+const FRAME_CALLBACK_IDLE_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How long `Runtime.stop()` waits for the frame callbacks to finish with the
+/// frames acquired before the runtime stopped.
+const FRAME_CALLBACK_DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+type FrameCallbackError = Arc<Mutex<Option<PyErr>>>;
+
+/// A worker thread that reads frames from a stream and passes them to a
+/// Python callback.
+struct FrameCallback {
+    /// Set to ask the worker to exit. Also set by the worker when it exits.
+    is_closed: Arc<AtomicBool>,
+    /// Incremented each time the worker finds the runtime stopped and the
+    /// stream empty, or is paused by an error.
+    drained: Arc<AtomicU64>,
+    /// The first exception raised by the callback or while reading the
+    /// stream, raised again by the next call to `Runtime.stop()`.
+    error: FrameCallbackError,
+    handle: std::thread::JoinHandle<()>,
+}
+
+impl FrameCallback {
+    /// Starts the worker. If given, `previous` is the worker of the callback
+    /// this one replaces, which is joined before the stream is read.
+    fn new(
+        runtime: Arc<RawRuntime>,
+        stream_id: u32,
+        callback: PyObject,
+        batch: usize,
+        previous: Option<std::thread::JoinHandle<()>>,
+    ) -> Result<Self> {
+        let is_closed = Arc::new(AtomicBool::new(false));
+        let drained = Arc::new(AtomicU64::new(0));
+        let error = FrameCallbackError::default();
+        let handle = {
+            let is_closed = is_closed.clone();
+            let drained = drained.clone();
+            let error = error.clone();
+            std::thread::Builder::new()
+                .name(format!("acquire-callback-{}", stream_id))
+                .spawn(move || {
+                    if let Some(previous) = previous {
+                        if previous.join().is_err() {
+                            error!("Frame callback worker panicked");
+                        }
+                    }
+                    frame_callback_worker(
+                        runtime, stream_id, callback, batch, &is_closed, &drained, &error,
+                    );
+                    is_closed.store(true, Ordering::Release);
+                })?
+        };
+        Ok(Self {
+            is_closed,
+            drained,
+            error,
+            handle,
+        })
+    }
+
+    /// Stops the worker and waits for it to exit.
+    ///
+    /// Called from the callback itself, the worker can't exit until the
+    /// callback returns, so its handle is returned instead.
+    fn close(self, py: Python<'_>) -> Option<std::thread::JoinHandle<()>> {
+        self.is_closed.store(true, Ordering::Release);
+        if self.handle.thread().id() == std::thread::current().id() {
+            return Some(self.handle);
+        }
+        Python::allow_threads(py, || {
+            if self.handle.join().is_err() {
+                error!("Frame callback worker panicked");
+            }
+        });
+        None
+    }
+}
+
+/// Raises the errors of the frame callbacks, ordered by stream ID.
+///
+/// Several errors are raised together, as an `ExceptionGroup` on Python 3.11
+/// and later. Before that, each one is chained as the `__context__` of the
+/// previous stream's error, and the first is raised.
+fn raise_frame_callback_errors(py: Python<'_>, errors: Vec<(u32, PyErr)>) -> PyResult<()> {
+    let mut errors: Vec<_> = errors.into_iter().map(|(_, e)| e).collect();
+    if errors.len() <= 1 {
+        return errors.pop().map_or(Ok(()), Err);
+    }
+
+    if let Ok(group) = py.import("builtins")?.getattr("BaseExceptionGroup") {
+        let exceptions: Vec<PyObject> = errors.iter().map(|e| e.value(py).into_py(py)).collect();
+        let group = group.call1(("Frame callbacks failed", exceptions))?;
+        return Err(PyErr::from_value(group));
+    }
+
+    for pair in errors.windows(2) {
+        // Appends to the end of the chain, keeping the error's own context.
+        let mut tail = pair[0].value(py).as_ref();
+        loop {
+            let context = tail.getattr("__context__")?;
+            if context.is_none() {
+                break;
+            }
+            tail = context;
+        }
+        tail.setattr("__context__", pair[1].value(py))?;
+    }
+    Err(errors.swap_remove(0))
+}
+
+/// Reads frames from the stream and calls `callback` with them until closed.
+///
+/// The frames passed to the callback are released once it returns, even if
+/// it raised. The first exception, from the callback or from reading the
+/// stream, is stored in `error`. Until it has been taken, no callbacks are
+/// called and the stream isn't read, so the frames acquired in the meantime
+/// stay queued.
+fn frame_callback_worker(
+    runtime: Arc<RawRuntime>,
+    stream_id: u32,
+    callback: PyObject,
+    batch: usize,
+    is_closed: &AtomicBool,
+    drained: &AtomicU64,
+    error: &Mutex<Option<PyErr>>,
+) {
+    while !is_closed.load(Ordering::Acquire) {
+        if error.lock().is_some() {
+            drained.fetch_add(1, Ordering::Release);
+            std::thread::sleep(FRAME_CALLBACK_IDLE_INTERVAL);
+            continue;
+        }
+        match RawAvailableData::map_wait(
+            &runtime,
+            stream_id,
+            batch,
+            Some(FRAME_STREAM_POLL_TIMEOUT),
+        ) {
+            Ok((Some(data), WaitStatus::TimedOut)) => data.release(),
+            Ok((Some(data), _)) => {
+                let (beg, end) = (data.beg, data.end);
+                let store = Arc::new(Mutex::new(Some(Arc::new(data))));
+                let mut frames = VideoFrameIteratorInner {
+                    store: store.clone(),
+                    cur: Mutex::new(beg),
+                    end,
+                };
+                let passed = Python::with_gil(|py| {
+                    let mut passed = 0;
+                    // The callback may have replaced or removed itself.
+                    while !is_closed.load(Ordering::Acquire) {
+                        let chunk: Vec<VideoFrame> = frames.by_ref().take(batch).collect();
+                        if chunk.is_empty() {
+                            break;
+                        }
+                        passed += chunk.len();
+                        if let Err(e) = callback.call1(py, (chunk,)) {
+                            *error.lock() = Some(e);
+                            break;
+                        }
+                    }
+                    passed
+                });
+                // Leaves the frames that weren't passed to the callback queued.
+                // Frames the callback kept arrays of stay held, without
+                // holding up the stream.
+                if let Some(data) = store.lock().take() {
+                    data.consume(passed);
+                    retire(Some(data));
+                }
+            }
+            Ok((None, WaitStatus::Stopped)) => {
+                drained.fetch_add(1, Ordering::Release);
+                std::thread::sleep(FRAME_CALLBACK_IDLE_INTERVAL);
+            }
+            Ok((None, _)) => {}
+            Err(e) => {
+                error!("[stream {}] Frame callback worker failed: {}", stream_id, e);
+                *error.lock() = Some(e.into());
+            }
+        }
+    }
+}
+
 struct VideoFrameIteratorInner {
-    store: Arc<Mutex<Option<RawAvailableData>>>,
+    store: FrameStore,
     cur: Mutex<NonNull<capi::VideoFrame>>,
     end: NonNull<capi::VideoFrame>,
 }
 
 unsafe impl Send for VideoFrameIteratorInner {}
 
+impl VideoFrameIteratorInner {
+    /// The region, once every frame has been handed out. It can then be
+    /// unmapped so that the stream can be read again while the frames stay
+    /// valid.
+    fn exhausted(&self) -> Option<Arc<RawAvailableData>> {
+        if *self.cur.lock() < self.end {
+            return None;
+        }
+        self.store.lock().clone()
+    }
+}
+
 impl Iterator for VideoFrameIteratorInner {
     type Item = VideoFrame;
 
@@ -446,6 +2090,87 @@ impl From<capi::VideoFrame_video_frame_timestamps_s> for VideoFrameTimestamps {
     }
 }
 
+/// Host clock readings taken together when the runtime was started.
+///
+/// Frame `acq_thread` timestamps are nanoseconds on the core's clock, which
+/// is read between two readings of Python's clocks, so no assumption is made
+/// about the clock the core uses. Hardware timestamps use device-specific
+/// units and are not converted.
+#[pyclass]
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub(crate) struct ClockCorrelation {
+    /// `time.monotonic_ns()` when the runtime was started.
+    #[pyo3(get, set)]
+    monotonic_ns: u64,
+
+    /// `time.time_ns()` at the same moment.
+    #[pyo3(get, set)]
+    wall_clock_ns: u64,
+
+    /// The core's clock, which `acq_thread` timestamps are read from, at the
+    /// same moment.
+    #[pyo3(get, set)]
+    acq_thread_ns: u64,
+}
+
+impl ClockCorrelation {
+    fn sample(py: Python<'_>) -> PyResult<Self> {
+        let time = py.import("time")?;
+        let read = || -> PyResult<(u64, u64)> {
+            Ok((
+                time.call_method0("monotonic_ns")?.extract()?,
+                time.call_method0("time_ns")?.extract()?,
+            ))
+        };
+
+        // Python's clocks are read on either side of the core's clock, and
+        // taken at the midpoint.
+        let before = read()?;
+        let acq_thread_ns = unsafe { capi::clock_tic(null_mut()) };
+        let after = read()?;
+        let midpoint = |before: u64, after: u64| before + after.saturating_sub(before) / 2;
+        Ok(Self {
+            monotonic_ns: midpoint(before.0, after.0),
+            wall_clock_ns: midpoint(before.1, after.1),
+            acq_thread_ns,
+        })
+    }
+
+    /// Nanoseconds from the sample to `timestamp`. Negative if the timestamp
+    /// is earlier.
+    fn elapsed_ns(&self, timestamp: u64) -> i128 {
+        timestamp as i128 - self.acq_thread_ns as i128
+    }
+}
+
+impl_plain_old_dict!(ClockCorrelation, {
+    /// Seconds from when the runtime was started to when the frame was
+    /// acquired.
+    fn seconds_since_start(&self, timestamps: &VideoFrameTimestamps) -> f64 {
+        self.elapsed_ns(timestamps.acq_thread) as f64 * 1e-9
+    }
+
+    /// When the frame was acquired, as seconds on the `time.monotonic()`
+    /// clock.
+    fn to_monotonic(&self, timestamps: &VideoFrameTimestamps) -> f64 {
+        (self.monotonic_ns as i128 + self.elapsed_ns(timestamps.acq_thread)) as f64 * 1e-9
+    }
+
+    /// When the frame was acquired, as a timezone-aware UTC `datetime`.
+    fn to_datetime<'py>(
+        &self,
+        timestamps: &VideoFrameTimestamps,
+        py: Python<'py>,
+    ) -> PyResult<&'py PyAny> {
+        let wall_clock_ns = self.wall_clock_ns as i128 + self.elapsed_ns(timestamps.acq_thread);
+        let datetime = py.import("datetime")?;
+        let utc = datetime.getattr("timezone")?.getattr("utc")?;
+        datetime
+            .getattr("datetime")?
+            .call_method1("fromtimestamp", (wall_clock_ns as f64 * 1e-9, utc))
+    }
+});
+
 #[pyclass]
 #[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
 pub(crate) struct VideoFrameMetadata {
@@ -454,10 +2179,75 @@ pub(crate) struct VideoFrameMetadata {
 
     #[pyo3(get, set)]
     timestamps: VideoFrameTimestamps,
+
+    /// The video stream the frame was read from.
+    #[pyo3(get, set)]
+    stream_id: u32,
+
+    /// `(planes, height, width, channels)`
+    #[pyo3(get, set)]
+    dims: (u32, u32, u32, u32),
+
+    /// Strides of `dims` in samples, as reported by the driver.
+    #[pyo3(get, set)]
+    strides: (i64, i64, i64, i64),
+
+    #[pyo3(get, set)]
+    sample_type: SampleType,
+
+    /// The size of the frame, including its header.
+    #[pyo3(get, set)]
+    bytes_of_frame: u64,
 }
 
 impl_plain_old_dict!(VideoFrameMetadata);
 
+impl VideoFrameMetadata {
+    fn from_header(header: &capi::VideoFrame, stream_id: u32) -> Result<Self> {
+        let dims = header.shape.dims;
+        let strides = header.shape.strides;
+        Ok(Self {
+            frame_id: header.frame_id,
+            timestamps: header.timestamps.into(),
+            stream_id,
+            dims: (dims.planes, dims.height, dims.width, dims.channels),
+            strides: (
+                strides.planes,
+                strides.height,
+                strides.width,
+                strides.channels,
+            ),
+            sample_type: header.shape.type_.try_into()?,
+            bytes_of_frame: header.bytes_of_frame as u64,
+        })
+    }
+}
+
+/// How a frame's image data is laid out in memory.
+#[pyclass]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub(crate) struct FrameShapeInfo {
+    /// `(planes, height, width, channels)`
+    #[pyo3(get)]
+    dims: (usize, usize, usize, usize),
+
+    /// Strides of `dims`, in samples.
+    #[pyo3(get)]
+    strides: (usize, usize, usize, usize),
+
+    #[pyo3(get)]
+    sample_type: SampleType,
+
+    #[pyo3(get)]
+    is_packed: bool,
+
+    /// The size of the image data in bytes.
+    #[pyo3(get)]
+    nbytes: usize,
+}
+
+impl_plain_old_dict!(@out FrameShapeInfo);
+
 enum SupportedImageView {
     U8(RawArrayView<u8, Ix4>),
     U16(RawArrayView<u16, Ix4>),
@@ -483,6 +2273,91 @@ impl SupportedImageView {
             SupportedImageView::F32(im) => cvt!(im),
         }
     }
+
+    /// Wraps the frame's memory in a read-only array without copying.
+    ///
+    /// The array keeps `container` alive as its base object.
+    ///
+    /// # Safety
+    ///
+    /// `container` must keep the memory backing the view alive.
+    unsafe fn to_pyobject_borrowed<'py>(
+        &self,
+        py: Python<'py>,
+        container: &'py PyAny,
+    ) -> PyResult<Py<PyAny>> {
+        match self {
+            SupportedImageView::U8(im) => borrow_readonly(py, im, container),
+            SupportedImageView::U16(im) => borrow_readonly(py, im, container),
+            SupportedImageView::I8(im) => borrow_readonly(py, im, container),
+            SupportedImageView::I16(im) => borrow_readonly(py, im, container),
+            SupportedImageView::F32(im) => borrow_readonly(py, im, container),
+        }
+    }
+}
+
+/// Wraps `view` in a read-only array that keeps `container` alive as its base
+/// object.
+///
+/// # Safety
+///
+/// `container` must keep the memory backing the view alive.
+unsafe fn borrow_readonly<T: Element, D: Dimension>(
+    py: Python<'_>,
+    view: &RawArrayView<T, D>,
+    container: &PyAny,
+) -> PyResult<Py<PyAny>> {
+    let array =
+        PyArray::borrow_from_array(&view.clone().deref_into_view(), container).to_object(py);
+    array
+        .getattr(py, "flags")?
+        .setattr(py, "writeable", false)?;
+    Ok(array)
+}
+
+/// Unpacks `count` samples of `bits` bits each from a little-endian bit
+/// stream, least significant bits first.
+///
+/// This is the layout of GenICam's `Mono10p`, `Mono12p` and `Mono14p`, where
+/// each sample starts at the bit after the previous one ends and the first
+/// sample's lowest bit is the lowest bit of the first byte. It is not the
+/// older `Mono12Packed`, which puts the high bits of each sample first.
+/// Samples that run past the end of `packed` are padded with zeros.
+fn unpack_samples(packed: &[u8], bits: usize, count: usize) -> Vec<u16> {
+    let mask = (1u32 << bits) - 1;
+    let mut out = Vec::with_capacity(count);
+    let mut bytes = packed.iter();
+    let mut acc: u32 = 0;
+    let mut nacc = 0;
+    while out.len() < count {
+        while nacc < bits {
+            acc |= (*bytes.next().unwrap_or(&0) as u32) << nacc;
+            nacc += 8;
+        }
+        out.push((acc & mask) as u16);
+        acc >>= bits;
+        nacc -= bits;
+    }
+    out
+}
+
+/// The base object of arrays returned by `VideoFrame.data(copy=False)`.
+///
+/// Keeps the frame's memory from being reused for as long as the array is
+/// alive, even after the `AvailableDataContext` it came from has exited.
+/// Reads of the stream skip past it, but it isn't released to the runtime
+/// until then. See `retire()`.
+#[pyclass]
+pub(crate) struct FrameDataGuard {
+    data: Option<Arc<RawAvailableData>>,
+}
+
+impl Drop for FrameDataGuard {
+    fn drop(&mut self) {
+        // The last guard releases the region, which waits on the runtime.
+        let data = self.data.take();
+        Python::with_gil(|py| py.allow_threads(move || drop(data)));
+    }
 }
 
 impl IntoDimension for capi::ImageShape_image_dims_s {
@@ -508,7 +2383,7 @@ impl IntoDimension for capi::ImageShape {
 
 #[pyclass]
 pub(crate) struct VideoFrame {
-    _store: Arc<Mutex<Option<RawAvailableData>>>,
+    _store: FrameStore,
     cur: NonNull<capi::VideoFrame>,
 }
 
@@ -517,63 +2392,558 @@ unsafe impl Send for VideoFrame {}
 #[pymethods]
 impl VideoFrame {
     fn metadata(slf: PyRef<'_, Self>) -> PyResult<VideoFrameMetadata> {
-        if (*slf._store.lock()).is_none() {
+        let stream_id = match &*slf._store.lock() {
+            Some(data) => data.stream_id,
+            None => {
+                return Err(PyRuntimeError::new_err(
+                    "VideoFrame is not valid outside of context",
+                ))
+            }
+        };
+        Ok(VideoFrameMetadata::from_header(
+            unsafe { slf.cur.as_ref() },
+            stream_id,
+        )?)
+    }
+
+    #[pyo3(signature = (copy=true, raw=false))]
+    fn data<'py>(&self, py: Python<'py>, copy: bool, raw: bool) -> PyResult<Py<PyAny>> {
+        if (*self._store.lock()).is_none() {
             return Err(PyRuntimeError::new_err(
                 "VideoFrame is not valid outside of context",
             ));
         }
-        let cur = slf.cur.as_ptr();
-        let meta = unsafe {
-            VideoFrameMetadata {
-                frame_id: (*cur).frame_id,
-                timestamps: (*cur).timestamps.into(),
-            }
-        };
-        Ok(meta)
+        unsafe { frame_data(py, self.cur, copy, raw, || Ok(self.guard(py)?.as_ref())) }
     }
 
-    fn data<'py>(&self, py: Python<'py>) -> PyResult<Py<PyAny>> {
+    #[pyo3(signature = (*, stream=None, max_version=None, dl_device=None, copy=None))]
+    fn __dlpack__(
+        &self,
+        py: Python<'_>,
+        stream: Option<&PyAny>,
+        max_version: Option<(u32, u32)>,
+        dl_device: Option<(i32, i32)>,
+        copy: Option<bool>,
+    ) -> PyResult<PyObject> {
+        // Frames are in host memory, so there is no stream to synchronize
+        // with.
+        let _ = stream;
+        let request = dlpack::check_request(dl_device, max_version, copy)?;
+        if request.copy {
+            let owned = PyCell::new(py, self.detach(py)?)?;
+            return OwnedVideoFrame::export(owned, request);
+        }
+        // Pin the region before reading the header.
+        let guard = self.guard(py)?;
+        let layout = self.layout()?;
+        unsafe { frame_capsule(py, self.cur, layout, guard.to_object(py), request) }
+    }
+
+    /// Describes how the frame's image data is laid out in memory.
+    fn shape_info(&self) -> PyResult<FrameShapeInfo> {
         if (*self._store.lock()).is_none() {
             return Err(PyRuntimeError::new_err(
                 "VideoFrame is not valid outside of context",
             ));
         }
-        let cur = self.cur.as_ptr();
+        Ok(self.layout()?.into())
+    }
 
-        macro_rules! gen_match {
-            ($x:expr, $($A:ident => $B:ident),+) => {
-                {
-                let sh=(*cur).shape;
-                match $x{
-                    $(
-                        capi::$A => Ok(SupportedImageView::$B(RawArrayView::from_shape_ptr(
-                            sh.into_dimension(),
-                            (*cur).data.as_ptr() as _,
-                        ))),
-                    )+
-                    _ => Err(anyhow!(
-                        "Unexpected image pixel type. Got value {}",
-                        (*cur).shape.type_
-                    )),
-                }
+    /// Copies the frame into a buffer owned by the returned frame, which
+    /// stays valid after the context exits.
+    fn detach(&self, py: Python<'_>) -> PyResult<OwnedVideoFrame> {
+        let data = self.pin()?;
+        let cur = self.cur.as_ptr() as usize;
+        Ok(Python::allow_threads(py, move || {
+            OwnedVideoFrame::copy(&data, cur as _)
+        }))
+    }
+
+    fn __dlpack_device__(&self) -> (i32, i32) {
+        (dlpack::DL_CPU, 0)
+    }
+}
+
+/// How the samples of a frame are stored.
+struct FrameLayout {
+    sample_type: SampleType,
+    /// `(planes, height, width, channels)`
+    dims: Ix4,
+    /// Strides of `dims`, in samples.
+    strides: Ix4,
+    /// Whether samples with fewer than 16 significant bits are packed
+    /// together rather than stored in 16 bits each.
+    is_packed: bool,
+    /// The size of the image data.
+    nbytes: usize,
+}
+
+impl FrameLayout {
+    /// Determines how the samples of the frame with `header` are stored.
+    ///
+    /// Drivers may deliver 10, 12 and 14 bit samples either packed, as
+    /// `unpack_samples()` expects, or in 16 bits each. They are taken to be
+    /// packed if the frame is too small to hold 16 bit samples.
+    ///
+    /// Strides that are all zero are taken to mean a contiguous layout.
+    fn of(header: &capi::VideoFrame) -> Result<Self> {
+        let sample_type: SampleType = header.shape.type_.try_into()?;
+        let dims = header.shape.into_dimension();
+        let count = dims.size();
+        let available = (header.bytes_of_frame as usize)
+            .saturating_sub(std::mem::size_of::<capi::VideoFrame>());
+
+        let (_, height, width, channels) = dims.into_pattern();
+        let contiguous = Dim([height * width * channels, width * channels, channels, 1]);
+        let given = header.shape.strides;
+        let strides = match [given.planes, given.height, given.width, given.channels] {
+            [0, 0, 0, 0] => contiguous,
+            strides if strides.iter().any(|&s| s < 0) => {
+                return Err(anyhow!(
+                    "Frame {} has negative strides, which are not supported",
+                    header.frame_id
+                ))
             }
-            };
+            strides => Dim(strides.map(|s| s as usize)),
+        };
+        // One past the furthest sample.
+        let extent = if count == 0 {
+            0
+        } else {
+            1 + dims
+                .slice()
+                .iter()
+                .zip(strides.slice())
+                .map(|(d, s)| (d - 1) * s)
+                .sum::<usize>()
+        };
+
+        let unpacked = extent * sample_type.bytes_per_sample();
+        let packed = (count * sample_type.bits_per_sample() + 7) / 8;
+        let is_packed = sample_type.bits_per_sample() % 8 != 0 && available < unpacked;
+        if is_packed && strides != contiguous {
+            return Err(anyhow!(
+                "Frame {} has packed samples with padded strides, which are not supported",
+                header.frame_id
+            ));
+        }
+        let nbytes = if is_packed { packed } else { unpacked };
+        if available < nbytes {
+            return Err(anyhow!(
+                "Frame {} holds {} bytes of image data, but its shape requires {}",
+                header.frame_id,
+                available,
+                nbytes
+            ));
         }
+        Ok(FrameLayout {
+            sample_type,
+            dims,
+            strides,
+            is_packed,
+            nbytes,
+        })
+    }
+}
 
-        let array = unsafe {
-            gen_match!((*cur).shape.type_,
-                SampleType_SampleType_u8 => U8,
-                SampleType_SampleType_u16 => U16,
-                SampleType_SampleType_i8 => I8,
-                SampleType_SampleType_i16 => I16,
-                SampleType_SampleType_f32 => F32
-            )
+/// Copies the samples of a frame laid out with `strides` at `src` to `dst`,
+/// contiguously.
+///
+/// # Safety
+///
+/// `src` must be valid for reads of every sample `dims` and `strides`
+/// address, and `dst` for writes of `dims.size()` samples.
+unsafe fn copy_strided<T: Clone>(src: *const T, dims: Ix4, strides: Ix4, dst: *mut T) {
+    let src = RawArrayView::from_shape_ptr(dims.strides(strides), src);
+    let mut dst = RawArrayViewMut::from_shape_ptr(dims, dst);
+    dst.deref_into_view_mut().assign(&src.deref_into_view());
+}
+
+impl From<FrameLayout> for FrameShapeInfo {
+    fn from(layout: FrameLayout) -> Self {
+        FrameShapeInfo {
+            dims: layout.dims.into_pattern(),
+            strides: layout.strides.into_pattern(),
+            sample_type: layout.sample_type,
+            is_packed: layout.is_packed,
+            nbytes: layout.nbytes,
+        }
+    }
+}
+
+impl VideoFrame {
+    fn layout(&self) -> Result<FrameLayout> {
+        FrameLayout::of(unsafe { self.cur.as_ref() })
+    }
+
+    /// The frame's region, which stays mapped while it's held.
+    ///
+    /// Fails once the `AvailableDataContext` the frame came from has exited.
+    fn pin(&self) -> PyResult<Arc<RawAvailableData>> {
+        self._store
+            .lock()
+            .clone()
+            .ok_or_else(|| PyRuntimeError::new_err("VideoFrame is not valid outside of context"))
+    }
+
+    /// Creates the base object for arrays that borrow the frame's memory.
+    fn guard<'py>(&self, py: Python<'py>) -> PyResult<&'py PyCell<FrameDataGuard>> {
+        PyCell::new(
+            py,
+            FrameDataGuard {
+                data: Some(self.pin()?),
+            },
+        )
+    }
+}
+
+/// Returns the image data of the frame at `cur`, as `VideoFrame.data()`
+/// does.
+///
+/// Arrays that don't copy keep the object returned by `base` alive.
+///
+/// # Safety
+///
+/// `cur` must point to a complete frame that `base` keeps alive.
+unsafe fn frame_data<'py>(
+    py: Python<'py>,
+    cur: NonNull<capi::VideoFrame>,
+    copy: bool,
+    raw: bool,
+    base: impl FnOnce() -> PyResult<&'py PyAny>,
+) -> PyResult<Py<PyAny>> {
+    let cur = cur.as_ptr();
+    let layout = FrameLayout::of(&*cur)?;
+
+    if raw {
+        let view = RawArrayView::from_shape_ptr(layout.nbytes, (*cur).data.as_ptr() as *const u8);
+        return if copy {
+            Ok(view.deref_into_view().to_pyarray(py).to_object(py))
+        } else {
+            borrow_readonly(py, &view, base()?)
+        };
+    }
+
+    if layout.is_packed {
+        if !copy {
+            return Err(PyValueError::new_err(
+                "Packed samples can't be viewed without copying. Use copy=True or raw=True.",
+            ));
         }
-        .unwrap();
+        let packed = std::slice::from_raw_parts((*cur).data.as_ptr(), layout.nbytes);
+        let samples = unpack_samples(
+            packed,
+            layout.sample_type.bits_per_sample(),
+            layout.dims.size(),
+        );
+        let array = Array4::from_shape_vec(layout.dims, samples).map_err(anyhow::Error::from)?;
+        return Ok(array.into_pyarray(py).to_object(py));
+    }
 
+    macro_rules! gen_match {
+        ($x:expr, $($A:ident => $B:ident),+) => {
+            {
+            let sh=layout.dims.strides(layout.strides);
+            match $x{
+                $(
+                    capi::$A => Ok(SupportedImageView::$B(RawArrayView::from_shape_ptr(
+                        sh,
+                        (*cur).data.as_ptr() as _,
+                    ))),
+                )+
+                _ => Err(anyhow!(
+                    "Unexpected image pixel type. Got value {}",
+                    (*cur).shape.type_
+                )),
+            }
+        }
+        };
+    }
+
+    let array = gen_match!((*cur).shape.type_,
+        SampleType_SampleType_u8 => U8,
+        SampleType_SampleType_u16 => U16,
+        SampleType_SampleType_i8 => I8,
+        SampleType_SampleType_i16 => I16,
+        SampleType_SampleType_f32 => F32,
+        SampleType_SampleType_u10 => U16,
+        SampleType_SampleType_u12 => U16,
+        SampleType_SampleType_u14 => U16
+    )?;
+
+    if copy {
         Ok(array.to_pyobject(py))
+    } else {
+        array.to_pyobject_borrowed(py, base()?)
+    }
+}
+
+/// Exports the image data of the frame at `cur` in a DLPack capsule.
+///
+/// # Safety
+///
+/// `cur` must point to a complete frame that `owner` keeps alive.
+unsafe fn frame_capsule(
+    py: Python<'_>,
+    cur: NonNull<capi::VideoFrame>,
+    layout: FrameLayout,
+    owner: PyObject,
+    request: dlpack::Request,
+) -> PyResult<PyObject> {
+    if layout.is_packed {
+        return Err(PyBufferError::new_err(
+            "Frames with packed samples can't be exported",
+        ));
+    }
+    dlpack::to_capsule(
+        py,
+        cur.as_ref().data.as_ptr() as _,
+        layout.dims.into_pattern().into(),
+        layout.strides.into_pattern().into(),
+        layout.sample_type.into(),
+        owner,
+        request,
+    )
+}
+
+/// A copy of a frame that stays valid after the `AvailableData` context it
+/// came from exits.
+///
+/// The copy is held in a buffer that is reused for later copies once the
+/// frame is dropped.
+#[pyclass]
+pub(crate) struct OwnedVideoFrame {
+    buffer: FrameBuffer,
+    stream_id: u32,
+}
+
+impl OwnedVideoFrame {
+    /// Copies the frame at `cur`, which must be in `data`'s region.
+    fn copy(data: &RawAvailableData, cur: *const capi::VideoFrame) -> Self {
+        Self {
+            buffer: unsafe { data.runtime.frame_pool.copy_frame(cur) },
+            stream_id: data.stream_id,
+        }
+    }
+
+    /// Exports the frame's memory in a DLPack capsule that keeps `slf`
+    /// alive.
+    fn export(slf: &PyCell<Self>, request: dlpack::Request) -> PyResult<PyObject> {
+        let header = slf.borrow().buffer.header();
+        let layout = FrameLayout::of(unsafe { header.as_ref() })?;
+        unsafe { frame_capsule(slf.py(), header, layout, slf.to_object(slf.py()), request) }
+    }
+}
+
+#[pymethods]
+impl OwnedVideoFrame {
+    fn metadata(&self) -> PyResult<VideoFrameMetadata> {
+        Ok(VideoFrameMetadata::from_header(
+            unsafe { self.buffer.header().as_ref() },
+            self.stream_id,
+        )?)
+    }
+
+    /// Arrays that don't copy keep the frame alive.
+    #[pyo3(signature = (copy=true, raw=false))]
+    fn data(slf: &PyCell<Self>, copy: bool, raw: bool) -> PyResult<Py<PyAny>> {
+        let header = slf.borrow().buffer.header();
+        unsafe { frame_data(slf.py(), header, copy, raw, || Ok(slf.as_ref())) }
+    }
+
+    #[pyo3(signature = (*, stream=None, max_version=None, dl_device=None, copy=None))]
+    fn __dlpack__(
+        slf: &PyCell<Self>,
+        stream: Option<&PyAny>,
+        max_version: Option<(u32, u32)>,
+        dl_device: Option<(i32, i32)>,
+        copy: Option<bool>,
+    ) -> PyResult<PyObject> {
+        let _ = stream;
+        let request = dlpack::check_request(dl_device, max_version, copy)?;
+        if request.copy {
+            let frame = slf.borrow();
+            let owned = OwnedVideoFrame {
+                buffer: frame.buffer.clone(),
+                stream_id: frame.stream_id,
+            };
+            return Self::export(PyCell::new(slf.py(), owned)?, request);
+        }
+        Self::export(slf, request)
+    }
+
+    fn __dlpack_device__(&self) -> (i32, i32) {
+        (dlpack::DL_CPU, 0)
+    }
+
+    fn shape_info(&self) -> PyResult<FrameShapeInfo> {
+        Ok(FrameLayout::of(unsafe { self.buffer.header().as_ref() })?.into())
     }
 }
 
 // TODO: Is everything really Send
 // TODO: mark iterable and videoframe as things that can't be shared across threads
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A header for a `width` by `height` frame with `nbytes` of image data.
+    fn header(
+        sample_type: capi::SampleType,
+        width: u32,
+        height: u32,
+        nbytes: usize,
+    ) -> capi::VideoFrame {
+        let mut header: capi::VideoFrame = unsafe { std::mem::zeroed() };
+        header.shape.type_ = sample_type;
+        header.shape.dims.planes = 1;
+        header.shape.dims.height = height as _;
+        header.shape.dims.width = width as _;
+        header.shape.dims.channels = 1;
+        header.bytes_of_frame = (std::mem::size_of::<capi::VideoFrame>() + nbytes) as _;
+        header
+    }
+
+    #[test]
+    fn unpacks_10_bit_samples() {
+        let packed = [0xff, 0x07, 0xa0, 0x6a, 0x55];
+        assert_eq!(
+            unpack_samples(&packed, 10, 4),
+            vec![0x3ff, 0x001, 0x2aa, 0x155]
+        );
+    }
+
+    #[test]
+    fn unpacks_12_bit_samples() {
+        let packed = [0xbc, 0x3a, 0x12];
+        assert_eq!(unpack_samples(&packed, 12, 2), vec![0xabc, 0x123]);
+    }
+
+    #[test]
+    fn unpacks_14_bit_samples() {
+        let packed = [0xff, 0x3f, 0x8d, 0x04];
+        assert_eq!(unpack_samples(&packed, 14, 2), vec![0x3fff, 0x1234]);
+    }
+
+    #[test]
+    fn unpacks_a_trailing_partial_byte() {
+        // 3 samples of 12 bits end halfway through the last byte.
+        let packed = [0xbc, 0x3a, 0x12, 0x56, 0x04];
+        assert_eq!(unpack_samples(&packed, 12, 3), vec![0xabc, 0x123, 0x456]);
+    }
+
+    #[test]
+    fn pads_missing_bytes_with_zeros() {
+        let packed = [0xbc, 0x3a, 0x12, 0x56];
+        assert_eq!(unpack_samples(&packed, 12, 3), vec![0xabc, 0x123, 0x056]);
+    }
+
+    #[test]
+    fn detects_packed_samples() {
+        // 8 samples of 12 bits take 12 bytes packed and 16 unpacked.
+        let packed = header(capi::SampleType_SampleType_u12, 4, 2, 12);
+        let layout = FrameLayout::of(&packed).unwrap();
+        assert!(layout.is_packed);
+        assert_eq!(layout.nbytes, 12);
+
+        let unpacked = header(capi::SampleType_SampleType_u12, 4, 2, 16);
+        let layout = FrameLayout::of(&unpacked).unwrap();
+        assert!(!layout.is_packed);
+        assert_eq!(layout.nbytes, 16);
+    }
+
+    #[test]
+    fn never_takes_whole_byte_samples_as_packed() {
+        let frame = header(capi::SampleType_SampleType_u16, 4, 2, 16);
+        assert!(!FrameLayout::of(&frame).unwrap().is_packed);
+
+        let short = header(capi::SampleType_SampleType_u16, 4, 2, 12);
+        assert!(FrameLayout::of(&short).is_err());
+    }
+
+    /// Sets the strides of `header`, in samples.
+    fn with_strides(mut header: capi::VideoFrame, strides: [i64; 4]) -> capi::VideoFrame {
+        let [planes, height, width, channels] = strides;
+        header.shape.strides.planes = planes as _;
+        header.shape.strides.height = height as _;
+        header.shape.strides.width = width as _;
+        header.shape.strides.channels = channels as _;
+        header
+    }
+
+    #[test]
+    fn keeps_padded_row_strides() {
+        // Rows of 3 samples padded to 4. The last row isn't padded.
+        let frame = with_strides(
+            header(capi::SampleType_SampleType_u16, 3, 2, 14),
+            [8, 4, 1, 1],
+        );
+        let layout = FrameLayout::of(&frame).unwrap();
+        assert_eq!(layout.strides, Dim([8, 4, 1, 1]));
+        assert_eq!(layout.dims, Dim([1, 2, 3, 1]));
+        assert!(!layout.is_packed);
+        assert_eq!(layout.nbytes, 14);
+    }
+
+    #[test]
+    fn takes_zero_strides_as_contiguous() {
+        let frame = header(capi::SampleType_SampleType_u8, 3, 2, 6);
+        assert_eq!(FrameLayout::of(&frame).unwrap().strides, Dim([6, 3, 1, 1]));
+    }
+
+    #[test]
+    fn rejects_frames_smaller_than_their_strides_reach() {
+        // The padded rows reach 7 samples, though only 6 are used.
+        let frame = with_strides(
+            header(capi::SampleType_SampleType_u8, 3, 2, 6),
+            [8, 4, 1, 1],
+        );
+        assert!(FrameLayout::of(&frame).is_err());
+
+        let frame = with_strides(
+            header(capi::SampleType_SampleType_u8, 3, 2, 7),
+            [8, 4, 1, 1],
+        );
+        assert_eq!(FrameLayout::of(&frame).unwrap().nbytes, 7);
+    }
+
+    #[test]
+    fn rejects_negative_strides() {
+        let frame = with_strides(
+            header(capi::SampleType_SampleType_u8, 3, 2, 8),
+            [8, -4, 1, 1],
+        );
+        assert!(FrameLayout::of(&frame).is_err());
+    }
+
+    #[test]
+    fn rejects_packed_samples_with_padded_strides() {
+        let frame = with_strides(
+            header(capi::SampleType_SampleType_u12, 3, 2, 9),
+            [8, 4, 1, 1],
+        );
+        assert!(FrameLayout::of(&frame).is_err());
+    }
+
+    #[test]
+    fn copies_padded_rows_contiguously() {
+        let src: [u16; 7] = [0, 1, 2, 0xffff, 3, 4, 5];
+        let mut dst = [0u16; 6];
+        unsafe {
+            copy_strided(
+                src.as_ptr(),
+                Dim([1, 2, 3, 1]),
+                Dim([8, 4, 1, 1]),
+                dst.as_mut_ptr(),
+            );
+        }
+        assert_eq!(dst, [0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn rejects_frames_too_small_even_when_packed() {
+        let frame = header(capi::SampleType_SampleType_u10, 4, 2, 9);
+        assert!(FrameLayout::of(&frame).is_err());
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
     capi,
     components::macros::{cvt, impl_plain_old_dict},
+    error::{ApiCall, ErrorKind},
 };
 use anyhow::{anyhow, Result};
 use pyo3::prelude::*;
@@ -378,7 +379,7 @@ impl TryFrom<&StorageProperties> for capi::StorageProperties {
         };
 
         // This copies the string into a buffer owned by the return value.
-        if !unsafe {
+        ApiCall::new("storage_properties_init", ErrorKind::Storage).run(|| unsafe {
             capi::storage_properties_init(
                 &mut out,
                 value.first_frame_id,
@@ -392,9 +393,12 @@ impl TryFrom<&StorageProperties> for capi::StorageProperties {
                 },
                 value.acquisition_dimensions.len() as u8,
             ) == 1
-        } {
-            Err(anyhow::anyhow!("Failed to initialize storage properties."))
-        } else if !unsafe {
+        })?;
+        ApiCall::new(
+            "storage_properties_set_access_key_and_secret",
+            ErrorKind::Storage,
+        )
+        .run(|| unsafe {
             capi::storage_properties_set_access_key_and_secret(
                 &mut out,
                 access_key_id,
@@ -402,36 +406,37 @@ impl TryFrom<&StorageProperties> for capi::StorageProperties {
                 secret_access_key,
                 bytes_of_secret_access_key as _,
             ) == 1
-        } {
-            Err(anyhow::anyhow!(
-                "Failed to set access key id and secret access key."
-            ))
-        } else if !unsafe {
+        })?;
+        ApiCall::new(
+            "storage_properties_set_enable_multiscale",
+            ErrorKind::Storage,
+        )
+        .run(|| unsafe {
             capi::storage_properties_set_enable_multiscale(&mut out, value.enable_multiscale as u8)
                 == 1
-        } {
-            Err(anyhow::anyhow!("Failed to set multiscale settings."))
-        } else {
-            // initialize each dimension separately
-            for (i, pydim) in value.acquisition_dimensions.iter().enumerate() {
-                let dim = Python::with_gil(|py| -> PyResult<_> {
-                    let storage_dim: StorageDimension = pydim.extract(py)?;
-                    Ok(storage_dim)
-                })?;
-
-                // Careful: x needs to live long enough
-                let x = if let Some(name) = &dim.name {
-                    Some(CString::new(name.as_str())?)
-                } else {
-                    None
-                };
-                let (name, bytes_of_name) = if let Some(ref x) = x {
-                    (x.as_ptr(), x.to_bytes_with_nul().len())
-                } else {
-                    (null(), 0)
-                };
-
-                if !unsafe {
+        })?;
+
+        // initialize each dimension separately
+        for (i, pydim) in value.acquisition_dimensions.iter().enumerate() {
+            let dim = Python::with_gil(|py| -> PyResult<_> {
+                let storage_dim: StorageDimension = pydim.extract(py)?;
+                Ok(storage_dim)
+            })?;
+
+            // Careful: x needs to live long enough
+            let x = if let Some(name) = &dim.name {
+                Some(CString::new(name.as_str())?)
+            } else {
+                None
+            };
+            let (name, bytes_of_name) = if let Some(ref x) = x {
+                (x.as_ptr(), x.to_bytes_with_nul().len())
+            } else {
+                (null(), 0)
+            };
+
+            ApiCall::new("storage_properties_set_dimension", ErrorKind::Storage).run(
+                || unsafe {
                     capi::storage_properties_set_dimension(
                         &mut out,
                         i.try_into().unwrap(),
@@ -442,13 +447,11 @@ impl TryFrom<&StorageProperties> for capi::StorageProperties {
                         dim.chunk_size_px,
                         dim.shard_size_chunks,
                     ) == 1
-                } {
-                    return Err(anyhow::anyhow!("Failed to set storage dimension."));
-                }
-            }
-
-            Ok(out)
+                },
+            )?;
         }
+
+        Ok(out)
     }
 }
 
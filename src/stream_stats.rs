@@ -0,0 +1,279 @@
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use crate::components::macros::impl_plain_old_dict;
+
+/// The number of most recently consumed frames the frame rate is estimated
+/// from.
+const FRAME_RATE_WINDOW: usize = 32;
+
+/// The maximum number of frame id anomalies kept for each stream.
+const MAX_FRAME_ID_ANOMALIES: usize = 1024;
+
+/// How a frame's id departed from the expected sequence.
+#[pyclass]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FrameIdAnomalyKind {
+    /// Ids were skipped, so frames were dropped.
+    Gap,
+    /// The id was the same as the previous frame's.
+    Repeat,
+    /// The id was smaller than the previous frame's.
+    OutOfOrder,
+}
+
+/// A frame whose id did not directly follow the previously read frame's.
+#[pyclass]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FrameIdAnomaly {
+    #[pyo3(get)]
+    kind: FrameIdAnomalyKind,
+
+    /// The id of the frame read before this one.
+    #[pyo3(get)]
+    previous_frame_id: u64,
+
+    #[pyo3(get)]
+    frame_id: u64,
+
+    /// The number of frames missing between the two, for gaps.
+    #[pyo3(get)]
+    missing: u64,
+}
+
+impl_plain_old_dict!(@out FrameIdAnomaly);
+
+/// Counters describing how data flows through a video stream.
+#[pyclass]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub(crate) struct StreamStats {
+    /// Frames seen in the stream's queue: those consumed plus those still
+    /// queued. A lower bound on the frames the runtime wrote, since frames
+    /// that are overwritten or dropped before a map aren't seen.
+    #[pyo3(get)]
+    frames_observed: u64,
+
+    /// Frames read from the queue and released back to the runtime.
+    #[pyo3(get)]
+    frames_consumed: u64,
+
+    /// Bytes of frame data released back to the runtime.
+    #[pyo3(get)]
+    bytes_consumed: u64,
+
+    /// Frames in the queue the last time it was mapped, less the frames
+    /// consumed since.
+    #[pyo3(get)]
+    queued_frames: u64,
+
+    /// The largest number of frames observed in the queue.
+    #[pyo3(get)]
+    max_queued_frames: u64,
+
+    /// Frames missing from gaps in the frame ids.
+    #[pyo3(get)]
+    frames_dropped: u64,
+
+    /// Frames per second over the most recently consumed frames, from their
+    /// acquisition thread timestamps.
+    #[pyo3(get)]
+    frame_rate_hz: f64,
+}
+
+impl_plain_old_dict!(@out StreamStats, {
+    /// Alias of `frames_observed`.
+    #[getter]
+    fn frames_produced(&self) -> u64 {
+        self.frames_observed
+    }
+
+    /// Alias of `bytes_consumed`.
+    #[getter]
+    fn bytes_mapped(&self) -> u64 {
+        self.bytes_consumed
+    }
+});
+
+/// Running counters for one stream, updated as regions are mapped and
+/// unmapped.
+#[derive(Debug, Default)]
+pub(crate) struct StreamCounters {
+    stats: StreamStats,
+    /// Acquisition thread timestamps (ns) of the most recently consumed
+    /// frames.
+    recent_timestamps: VecDeque<u64>,
+    /// The id of the most recently consumed frame.
+    last_frame_id: Option<u64>,
+    anomalies: VecDeque<FrameIdAnomaly>,
+}
+
+impl StreamCounters {
+    pub(crate) fn stats(&self) -> StreamStats {
+        self.stats.clone()
+    }
+
+    pub(crate) fn anomalies(&self) -> Vec<FrameIdAnomaly> {
+        self.anomalies.iter().copied().collect()
+    }
+
+    /// Records that the queue held `frame_count` frames when it was mapped.
+    ///
+    /// Only called when the mapped region changed, so polling an idle queue
+    /// costs nothing here.
+    pub(crate) fn on_map(&mut self, frame_count: usize) {
+        let stats = &mut self.stats;
+        stats.queued_frames = frame_count as u64;
+        stats.max_queued_frames = stats.max_queued_frames.max(stats.queued_frames);
+        stats.frames_observed = stats
+            .frames_observed
+            .max(stats.frames_consumed + stats.queued_frames);
+    }
+
+    /// Records frames released back to the runtime, given their ids and
+    /// acquisition thread timestamps.
+    ///
+    /// Returns the frame id anomalies found among them.
+    pub(crate) fn on_consume(
+        &mut self,
+        frames: impl IntoIterator<Item = (u64, u64)>,
+        bytes: usize,
+    ) -> Vec<FrameIdAnomaly> {
+        let mut frame_count = 0;
+        let mut anomalies = Vec::new();
+        for (frame_id, timestamp) in frames {
+            if let Some(anomaly) = self.check_frame_id(frame_id) {
+                if self.anomalies.len() == MAX_FRAME_ID_ANOMALIES {
+                    self.anomalies.pop_front();
+                }
+                self.anomalies.push_back(anomaly);
+                anomalies.push(anomaly);
+            }
+
+            if self.recent_timestamps.len() == FRAME_RATE_WINDOW {
+                self.recent_timestamps.pop_front();
+            }
+            self.recent_timestamps.push_back(timestamp);
+            frame_count += 1;
+        }
+
+        let stats = &mut self.stats;
+        stats.frames_consumed += frame_count;
+        stats.bytes_consumed += bytes as u64;
+        stats.queued_frames = stats.queued_frames.saturating_sub(frame_count);
+        stats.frames_observed = stats
+            .frames_observed
+            .max(stats.frames_consumed + stats.queued_frames);
+
+        if let (Some(first), Some(last)) = (
+            self.recent_timestamps.front(),
+            self.recent_timestamps.back(),
+        ) {
+            if last > first {
+                stats.frame_rate_hz =
+                    (self.recent_timestamps.len() - 1) as f64 * 1e9 / (last - first) as f64;
+            }
+        }
+        anomalies
+    }
+
+    fn check_frame_id(&mut self, frame_id: u64) -> Option<FrameIdAnomaly> {
+        let previous_frame_id = self.last_frame_id.replace(frame_id)?;
+        let (kind, missing) = if frame_id == previous_frame_id + 1 {
+            return None;
+        } else if frame_id > previous_frame_id {
+            (FrameIdAnomalyKind::Gap, frame_id - previous_frame_id - 1)
+        } else if frame_id == previous_frame_id {
+            (FrameIdAnomalyKind::Repeat, 0)
+        } else {
+            (FrameIdAnomalyKind::OutOfOrder, 0)
+        };
+        self.stats.frames_dropped += missing;
+        Some(FrameIdAnomaly {
+            kind,
+            previous_frame_id,
+            frame_id,
+            missing,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn anomalies(frame_ids: &[u64]) -> (Vec<FrameIdAnomaly>, StreamStats) {
+        let mut counters = StreamCounters::default();
+        let found = frame_ids
+            .iter()
+            .filter_map(|&frame_id| counters.check_frame_id(frame_id))
+            .collect();
+        (found, counters.stats())
+    }
+
+    #[test]
+    fn consecutive_ids_are_not_anomalies() {
+        let (found, stats) = anomalies(&[4, 5, 6, 7]);
+        assert!(found.is_empty());
+        assert_eq!(stats.frames_dropped, 0);
+    }
+
+    #[test]
+    fn first_id_is_not_an_anomaly() {
+        let (found, _) = anomalies(&[100]);
+        assert!(found.is_empty());
+    }
+
+    #[test]
+    fn skipped_ids_are_a_gap() {
+        let (found, stats) = anomalies(&[1, 2, 5, 6, 8]);
+        assert_eq!(
+            found,
+            vec![
+                FrameIdAnomaly {
+                    kind: FrameIdAnomalyKind::Gap,
+                    previous_frame_id: 2,
+                    frame_id: 5,
+                    missing: 2,
+                },
+                FrameIdAnomaly {
+                    kind: FrameIdAnomalyKind::Gap,
+                    previous_frame_id: 6,
+                    frame_id: 8,
+                    missing: 1,
+                },
+            ]
+        );
+        assert_eq!(stats.frames_dropped, 3);
+    }
+
+    #[test]
+    fn same_id_is_a_repeat() {
+        let (found, stats) = anomalies(&[3, 3, 4]);
+        assert_eq!(
+            found,
+            vec![FrameIdAnomaly {
+                kind: FrameIdAnomalyKind::Repeat,
+                previous_frame_id: 3,
+                frame_id: 3,
+                missing: 0,
+            }]
+        );
+        assert_eq!(stats.frames_dropped, 0);
+    }
+
+    #[test]
+    fn smaller_id_is_out_of_order() {
+        let (found, stats) = anomalies(&[7, 2, 3]);
+        assert_eq!(
+            found,
+            vec![FrameIdAnomaly {
+                kind: FrameIdAnomalyKind::OutOfOrder,
+                previous_frame_id: 7,
+                frame_id: 2,
+                missing: 0,
+            }]
+        );
+        assert_eq!(stats.frames_dropped, 0);
+    }
+}
@@ -0,0 +1,92 @@
+use anyhow::{anyhow, Result};
+use log::warn;
+use pyo3::{exceptions::PyValueError, prelude::*};
+use serde::{Deserialize, Deserializer};
+use std::fmt::Write;
+
+/// Deserializes a `T`, naming the setting at fault when a value has the wrong
+/// type.
+///
+/// Keys that don't name a setting, e.g. a misspelled `exposure_time_ms`, are
+/// errors when `strict` is set. Otherwise they are logged and ignored.
+pub(crate) fn deserialize<'de, D, T>(deserializer: D, strict: bool) -> Result<T>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    let mut unknown = Vec::new();
+    let mut track = serde_path_to_error::Track::new();
+    let deserializer =
+        serde_ignored::Deserializer::new(deserializer, |path| unknown.push(ignored_path(&path)));
+    let deserializer = serde_path_to_error::Deserializer::new(deserializer, &mut track);
+
+    let value = T::deserialize(deserializer).map_err(|e| {
+        let path = error_path(&track.path());
+        if path.is_empty() {
+            anyhow!("{}", e)
+        } else {
+            anyhow!("Invalid value for {}: {}", path, e)
+        }
+    })?;
+    if unknown.is_empty() {
+        Ok(value)
+    } else if strict {
+        Err(anyhow!("Unknown setting(s): {}", unknown.join(", ")))
+    } else {
+        warn!("Ignoring unknown setting(s): {}", unknown.join(", "));
+        Ok(value)
+    }
+}
+
+/// Deserializes a `T` from the keyword arguments of a constructor, raising
+/// `ValueError` on mistyped keys, and on unknown keys when `strict` is set.
+pub(crate) fn depythonize<'de, T: Deserialize<'de>>(obj: &'de PyAny, strict: bool) -> PyResult<T> {
+    let mut depythonizer = pythonize::Depythonizer::from_object(obj);
+    deserialize(&mut depythonizer, strict).map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+/// Appends `key` to a path like `video[0].camera`.
+pub(crate) fn push_key(path: &mut String, key: &str) {
+    if !path.is_empty() {
+        path.push('.');
+    }
+    path.push_str(key);
+}
+
+pub(crate) fn push_index(path: &mut String, index: usize) {
+    write!(path, "[{}]", index).expect("writing to a String can't fail");
+}
+
+fn ignored_path(path: &serde_ignored::Path) -> String {
+    use serde_ignored::Path as P;
+    match path {
+        P::Root => String::new(),
+        P::Seq { parent, index } => {
+            let mut out = ignored_path(parent);
+            push_index(&mut out, *index);
+            out
+        }
+        P::Map { parent, key } => {
+            let mut out = ignored_path(parent);
+            push_key(&mut out, key);
+            out
+        }
+        P::Some { parent } | P::NewtypeStruct { parent } | P::NewtypeVariant { parent } => {
+            ignored_path(parent)
+        }
+    }
+}
+
+fn error_path(path: &serde_path_to_error::Path) -> String {
+    use serde_path_to_error::Segment as S;
+    let mut out = String::new();
+    for segment in path.iter() {
+        match segment {
+            S::Seq { index } => push_index(&mut out, *index),
+            S::Map { key } => push_key(&mut out, key),
+            S::Enum { variant } => push_key(&mut out, variant),
+            S::Unknown => push_key(&mut out, "?"),
+        }
+    }
+    out
+}
@@ -0,0 +1,287 @@
+use anyhow::Result;
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    camera::CameraProperties,
+    capi,
+    components::{macros::impl_plain_old_dict, SampleType},
+    core_properties::{Properties, VideoStream},
+    storage::StorageProperties,
+};
+
+/// What is wrong with a setting.
+#[pyclass]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ViolationKind {
+    /// A value is outside the range the device supports.
+    OutOfRange,
+    /// The camera does not support the pixel type.
+    UnsupportedPixelType,
+    /// A trigger uses a line the camera does not have.
+    InvalidTriggerLine,
+    /// The storage device does not support a requested feature.
+    UnsupportedStorageFeature,
+    /// The device is not the one currently configured, so its capabilities
+    /// are unknown and its settings were not checked.
+    DeviceNotConfigured,
+}
+
+/// A setting that the configured devices can't honor.
+#[pyclass]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigurationViolation {
+    #[pyo3(get)]
+    stream_id: u32,
+
+    /// Path to the setting from the `Properties`, e.g.
+    /// `video[0].camera.settings.exposure_time_us`.
+    #[pyo3(get)]
+    field: String,
+
+    #[pyo3(get)]
+    kind: ViolationKind,
+
+    #[pyo3(get)]
+    message: String,
+}
+
+impl_plain_old_dict!(@out ConfigurationViolation);
+
+/// Collects the violations found in one video stream's settings.
+struct Checker {
+    stream_id: u32,
+    violations: Vec<ConfigurationViolation>,
+}
+
+impl Checker {
+    fn push(&mut self, field: &str, kind: ViolationKind, message: String) {
+        self.violations.push(ConfigurationViolation {
+            stream_id: self.stream_id,
+            field: format!("video[{}].{}", self.stream_id, field),
+            kind,
+            message,
+        });
+    }
+
+    /// Checks that `value` is within `property`'s range.
+    ///
+    /// Devices report an empty range for properties they don't describe, so
+    /// those are not checked.
+    fn check_range(&mut self, field: &str, value: f64, property: &capi::Property) {
+        let (low, high) = (property.low as f64, property.high as f64);
+        if high > low && !(low..=high).contains(&value) {
+            self.push(
+                field,
+                ViolationKind::OutOfRange,
+                format!(
+                    "{} is outside the supported range [{}, {}]",
+                    value, low, high
+                ),
+            );
+        }
+    }
+
+    /// Checks that `requested` is the configured device, whose capabilities
+    /// are the only ones known. Returns whether it is.
+    fn check_device(
+        &mut self,
+        field: &str,
+        requested: &capi::DeviceIdentifier,
+        configured: &capi::DeviceIdentifier,
+    ) -> bool {
+        let same = requested.kind == configured.kind
+            && requested.driver_id == configured.driver_id
+            && requested.device_id == configured.device_id;
+        if !same {
+            let name = |ident: &capi::DeviceIdentifier| {
+                ident
+                    .name_as_string()
+                    .unwrap_or_else(|_| "<unnamed>".to_owned())
+            };
+            self.push(
+                field,
+                ViolationKind::DeviceNotConfigured,
+                format!(
+                    "\"{}\" is not the configured device \"{}\"; apply it with \
+                     set_configuration() to check its settings",
+                    name(requested),
+                    name(configured)
+                ),
+            );
+        }
+        same
+    }
+
+    fn check_camera(
+        &mut self,
+        settings: &capi::CameraProperties,
+        meta: &capi::CameraPropertyMetadata,
+    ) {
+        self.check_range(
+            "camera.settings.exposure_time_us",
+            settings.exposure_time_us as f64,
+            &meta.exposure_time_us,
+        );
+
+        for (axis, offset, shape, offset_meta, shape_meta) in [
+            (
+                0,
+                settings.offset.x,
+                settings.shape.x,
+                &meta.offset.x,
+                &meta.shape.x,
+            ),
+            (
+                1,
+                settings.offset.y,
+                settings.shape.y,
+                &meta.offset.y,
+                &meta.shape.y,
+            ),
+        ] {
+            self.check_range(
+                &format!("camera.settings.offset[{}]", axis),
+                offset as f64,
+                offset_meta,
+            );
+            let extent = offset as f64 + shape as f64;
+            let max = shape_meta.high as f64;
+            if max > 0.0 && extent > max {
+                self.push(
+                    &format!("camera.settings.shape[{}]", axis),
+                    ViolationKind::OutOfRange,
+                    format!(
+                        "offset {} plus shape {} exceeds the sensor's {} pixels",
+                        offset, shape, max
+                    ),
+                );
+            }
+        }
+
+        let supported = meta.supported_pixel_types;
+        if supported != 0 && supported & (1 << settings.pixel_type) == 0 {
+            let pixel_type: Result<SampleType> = settings.pixel_type.try_into();
+            self.push(
+                "camera.settings.pixel_type",
+                ViolationKind::UnsupportedPixelType,
+                format!(
+                    "{} is not supported by the camera",
+                    pixel_type.map_or(settings.pixel_type.to_string(), |t| format!("{:?}", t))
+                ),
+            );
+        }
+
+        let line_count = meta.digital_lines.line_count;
+        let inputs = &settings.input_triggers;
+        let outputs = &settings.output_triggers;
+        for (name, trigger) in [
+            (
+                "input_triggers.acquisition_start",
+                &inputs.acquisition_start,
+            ),
+            ("input_triggers.frame_start", &inputs.frame_start),
+            ("input_triggers.exposure", &inputs.exposure),
+            ("output_triggers.exposure", &outputs.exposure),
+            ("output_triggers.frame_start", &outputs.frame_start),
+            ("output_triggers.trigger_wait", &outputs.trigger_wait),
+        ] {
+            if trigger.enable != 0 && trigger.line >= line_count {
+                self.push(
+                    &format!("camera.settings.{}.line", name),
+                    ViolationKind::InvalidTriggerLine,
+                    format!(
+                        "line {} does not exist; the camera has {} digital lines",
+                        trigger.line, line_count
+                    ),
+                );
+            }
+        }
+    }
+
+    fn check_storage(
+        &mut self,
+        py: Python<'_>,
+        settings: &StorageProperties,
+        meta: &capi::StoragePropertyMetadata,
+    ) {
+        for (i, dimension) in settings.acquisition_dimensions.iter().enumerate() {
+            let dimension = dimension.borrow(py);
+            if dimension.chunk_size_px > 0 && meta.chunking_is_supported != 1 {
+                self.push(
+                    &format!(
+                        "storage.settings.acquisition_dimensions[{}].chunk_size_px",
+                        i
+                    ),
+                    ViolationKind::UnsupportedStorageFeature,
+                    "chunking is not supported by the storage device".into(),
+                );
+            }
+            if dimension.shard_size_chunks > 0 && meta.sharding_is_supported != 1 {
+                self.push(
+                    &format!(
+                        "storage.settings.acquisition_dimensions[{}].shard_size_chunks",
+                        i
+                    ),
+                    ViolationKind::UnsupportedStorageFeature,
+                    "sharding is not supported by the storage device".into(),
+                );
+            }
+        }
+        if settings.enable_multiscale && meta.multiscale_is_supported != 1 {
+            self.push(
+                "storage.settings.enable_multiscale",
+                ViolationKind::UnsupportedStorageFeature,
+                "multiscale is not supported by the storage device".into(),
+            );
+        }
+    }
+}
+
+/// Checks each video stream in `properties` against the capabilities of the
+/// devices described by `meta`, which are those of `configured`.
+///
+/// `requested` is `properties` with its identifiers resolved. Devices that
+/// aren't the configured ones are reported rather than checked.
+pub(crate) fn validate(
+    py: Python<'_>,
+    properties: &Properties,
+    requested: &capi::AcquireProperties,
+    configured: &capi::AcquireProperties,
+    meta: &capi::AcquirePropertyMetadata,
+) -> Result<Vec<ConfigurationViolation>> {
+    let mut violations = Vec::new();
+    let streams: [&Py<VideoStream>; 2] = [&properties.video.0, &properties.video.1];
+    for (stream_id, stream) in streams.into_iter().enumerate() {
+        let mut checker = Checker {
+            stream_id: stream_id as u32,
+            violations: Vec::new(),
+        };
+        let (requested, configured) = (&requested.video[stream_id], &configured.video[stream_id]);
+        let meta = &meta.video[stream_id];
+        let stream = stream.borrow(py);
+
+        if checker.check_device(
+            "camera.identifier",
+            &requested.camera.identifier,
+            &configured.camera.identifier,
+        ) {
+            let camera = stream.camera.borrow(py);
+            let settings: CameraProperties = camera.settings.extract(py)?;
+            let settings: capi::CameraProperties = (&settings).try_into()?;
+            checker.check_camera(&settings, &meta.camera);
+        }
+
+        if checker.check_device(
+            "storage.identifier",
+            &requested.storage.identifier,
+            &configured.storage.identifier,
+        ) {
+            let storage = stream.storage.borrow(py);
+            checker.check_storage(py, &storage.settings.borrow(py), &meta.storage);
+        }
+
+        violations.append(&mut checker.violations);
+    }
+    Ok(violations)
+}
@@ -1,13 +1,26 @@
+import asyncio
+import gc
 import json
 import logging
 import os
+import sys
+import threading
 import time
-from datetime import timedelta
+import weakref
+from datetime import timedelta, timezone
 from time import sleep
 from typing import Any, Dict, List, Optional, Tuple
 
 import acquire
-from acquire import DeviceKind, DeviceState, Runtime, Trigger, PropertyType
+import numpy as np
+from acquire import (
+    DeviceKind,
+    DeviceState,
+    PropertyType,
+    Runtime,
+    StateChangeCause,
+    Trigger,
+)
 import pytest
 import tifffile
 
@@ -89,11 +102,60 @@ def test_select_empty_string(runtime: Runtime):
     assert runtime.device_manager().select(DeviceKind.Storage, "")
 
 
+def test_select_required_raises_device_not_found(runtime: Runtime):
+    dm = runtime.device_manager()
+    assert dm.select(DeviceKind.Camera, "no such camera") is None
+    with pytest.raises(acquire.DeviceNotFoundError, match="no such camera"):
+        dm.select(DeviceKind.Camera, "no such camera", required=True)
+    assert dm.select(DeviceKind.Storage, "Trash", required=True)
+
+
+def test_errors_are_not_carried_over(runtime: Runtime):
+    with pytest.raises(acquire.StateError) as failed_start:
+        runtime.start()
+    assert failed_start.value.messages
+    with pytest.raises(acquire.AcquireTimeoutError) as timed_out:
+        runtime.wait_for_state(DeviceState.Running, timeout_ms=10)
+    assert timed_out.value.messages == []
+    assert isinstance(timed_out.value, TimeoutError)
+    assert isinstance(timed_out.value, acquire.AcquireError)
+
+
 def test_zero_conf_start(runtime: Runtime):
     with pytest.raises(RuntimeError):
         runtime.start()
 
 
+def test_zero_conf_start_raises_state_error(runtime: Runtime):
+    with pytest.raises(acquire.StateError) as exc_info:
+        runtime.start()
+    assert isinstance(exc_info.value, acquire.AcquireError)
+    assert exc_info.value.api == "acquire_start"
+    assert exc_info.value.stream_id is None
+    assert isinstance(exc_info.value.messages, list)
+
+
+def test_log_records(runtime: Runtime):
+    runtime.clear_log_records()
+    assert runtime.get_log_records() == []
+
+    with pytest.raises(acquire.StateError):
+        runtime.start()
+
+    records = runtime.get_log_records()
+    assert len(records) > 0
+    errors = [r for r in records if r.level == acquire.LogLevel.Error]
+    assert len(errors) > 0
+    assert all(r.message for r in errors)
+    assert all(isinstance(r.line, int) for r in records)
+    assert records == sorted(records, key=lambda r: r.timestamp)
+
+    assert runtime.get_log_records(since=records[-1].timestamp) == []
+
+    runtime.clear_log_records()
+    assert runtime.get_log_records() == []
+
+
 def test_repeat_acq(runtime: Runtime):
     p = acquire.setup(runtime, "simulated: radial sin", "Trash")
     assert (
@@ -378,6 +440,1180 @@ def test_abort(runtime: Runtime):
     assert nframes < p.video[0].max_frame_count
 
 
+def test_wait_for_state(runtime: Runtime):
+    p = acquire.setup(runtime, "simulated.*empty", "Trash")
+    p.video[0].camera.settings.shape = (64, 48)
+    p.video[0].max_frame_count = 10
+    runtime.set_configuration(p)
+
+    changes = []
+    runtime.on_state_change(
+        lambda old, new, cause: changes.append((old, new, cause))
+    )
+
+    runtime.start()
+    runtime.wait_for_state(DeviceState.Armed, timeout_ms=20000)
+    runtime.stop()
+
+    assert changes == [
+        (DeviceState.Armed, DeviceState.Running, StateChangeCause.Start),
+        (DeviceState.Running, DeviceState.Armed, StateChangeCause.Finished),
+    ]
+
+
+def test_state_callbacks_report_cause(runtime: Runtime):
+    p = acquire.setup(runtime, "simulated.*empty", "Trash")
+    p.video[0].camera.settings.shape = (64, 48)
+    p.video[0].max_frame_count = 2**30
+    runtime.set_configuration(p)
+
+    changes = []
+    runtime.on_state_change(
+        lambda old, new, cause: changes.append((old, new, cause))
+    )
+    # A short acquisition that finishes before the watcher polls is still
+    # reported, and put down to the runtime rather than to `stop()`.
+    for max_frame_count, cause in (
+        (1, StateChangeCause.Finished),
+        (2**30, StateChangeCause.Stop),
+    ):
+        p.video[0].max_frame_count = max_frame_count
+        runtime.set_configuration(p)
+        runtime.start()
+        if cause == StateChangeCause.Finished:
+            runtime.wait_for_state(DeviceState.Armed, timeout_ms=20000)
+        runtime.stop()
+
+    assert changes == [
+        (DeviceState.Armed, DeviceState.Running, StateChangeCause.Start),
+        (DeviceState.Running, DeviceState.Armed, StateChangeCause.Finished),
+        (DeviceState.Armed, DeviceState.Running, StateChangeCause.Start),
+        (DeviceState.Running, DeviceState.Armed, StateChangeCause.Stop),
+    ]
+
+
+def test_state_callbacks_are_collected():
+    class Sentinel:
+        pass
+
+    def register():
+        runtime = acquire.Runtime()
+        sentinel = Sentinel()
+        # The callback refers back to the runtime that holds it.
+        runtime.on_state_change(lambda *_: (runtime, sentinel))
+        return weakref.ref(sentinel)
+
+    ref = register()
+    gc.collect()
+    assert ref() is None
+
+
+def test_wait_for_state_times_out(runtime: Runtime):
+    with pytest.raises(acquire.AcquireTimeoutError):
+        runtime.wait_for_state(DeviceState.Running, timeout_ms=50)
+
+
+def test_get_available_data_waits_for_frames(runtime: Runtime):
+    p = acquire.setup(runtime, "simulated.*empty", "Trash")
+    p.video[0].camera.settings.shape = (64, 48)
+    p.video[0].max_frame_count = 3
+    runtime.set_configuration(p)
+
+    # Not running yet, so there's nothing to wait for.
+    with runtime.get_available_data(0, min_frames=1) as packet:
+        assert packet.wait_status == acquire.WaitStatus.Stopped
+        assert packet.get_frame_count() == 0
+
+    runtime.start()
+    with runtime.get_available_data(
+        0, min_frames=1, timeout_ms=5000
+    ) as packet:
+        assert packet.wait_status == acquire.WaitStatus.Ready
+        assert packet.get_frame_count() >= 1
+    runtime.stop()
+
+
+def test_get_stream_stats(runtime: Runtime):
+    p = acquire.setup(runtime, "simulated.*empty", "Trash")
+    p.video[0].camera.settings.shape = (64, 48)
+    p.video[0].max_frame_count = 10
+    runtime.set_configuration(p)
+
+    stats = runtime.get_stream_stats(0)
+    assert stats.frames_observed == 0
+    assert stats.frames_consumed == 0
+
+    runtime.start()
+    nframes = 0
+    nbytes = 0
+    while nframes < p.video[0].max_frame_count:
+        with runtime.get_available_data(
+            0, min_frames=1, timeout_ms=5000
+        ) as packet:
+            for frame in packet.frames():
+                nframes += 1
+                nbytes += frame.data().nbytes
+    runtime.stop()
+
+    stats = runtime.get_stream_stats(0)
+    assert stats.frames_consumed == nframes
+    assert stats.frames_observed >= stats.frames_consumed
+    assert stats.bytes_consumed >= nbytes
+    assert stats.frames_produced == stats.frames_observed
+    assert stats.bytes_mapped == stats.bytes_consumed
+    assert stats.queued_frames == 0
+    assert stats.max_queued_frames >= 1
+    assert stats.frame_rate_hz > 0
+
+
+def test_frame_id_anomalies(runtime: Runtime, caplog):
+    p = acquire.setup(runtime, "simulated.*empty", "Trash")
+    p.video[0].camera.settings.shape = (64, 48)
+    p.video[0].max_frame_count = 10
+    runtime.set_configuration(p)
+    runtime.set_frame_id_warnings()
+
+    runtime.start()
+    frame_ids = []
+    while len(frame_ids) < p.video[0].max_frame_count:
+        with runtime.get_available_data(
+            0, min_frames=1, timeout_ms=5000
+        ) as packet:
+            frame_ids.extend(f.metadata().frame_id for f in packet.frames())
+    runtime.stop()
+
+    assert frame_ids == list(range(len(frame_ids)))
+    assert runtime.get_frame_id_anomalies(0) == []
+    assert runtime.get_stream_stats(0).frames_dropped == 0
+    assert "Frame id anomaly" not in caplog.text
+
+
+def test_set_frame_callback(runtime: Runtime):
+    p = acquire.setup(runtime, "simulated.*empty", "Trash")
+    p.video[0].camera.settings.shape = (64, 48)
+    p.video[0].max_frame_count = 10
+    runtime.set_configuration(p)
+
+    frame_ids = []
+    batch_sizes = []
+
+    def on_frames(frames):
+        batch_sizes.append(len(frames))
+        frame_ids.extend(f.metadata().frame_id for f in frames)
+
+    runtime.set_frame_callback(0, on_frames, batch=3)
+    runtime.start()
+    runtime.stop()
+    runtime.set_frame_callback(0, None)
+
+    assert frame_ids == list(range(p.video[0].max_frame_count))
+    assert all(1 <= n <= 3 for n in batch_sizes)
+
+
+def test_set_frame_callback_keeping_arrays(runtime: Runtime):
+    p = acquire.setup(runtime, "simulated: radial sin", "Trash")
+    p.video[0].camera.settings.shape = (64, 48)
+    p.video[0].max_frame_count = 5
+    runtime.set_configuration(p)
+
+    kept = []
+    frame_ids = []
+
+    def keeping(frames):
+        kept.extend(f.data(copy=False) for f in frames)
+        frame_ids.extend(f.metadata().frame_id for f in frames)
+
+    # Arrays kept from earlier frames don't hold up the callback.
+    runtime.set_frame_callback(0, keeping)
+    runtime.start()
+    runtime.stop()
+    runtime.set_frame_callback(0, None)
+
+    assert frame_ids == list(range(p.video[0].max_frame_count))
+    assert len(kept) == p.video[0].max_frame_count
+    kept.clear()
+
+
+def test_set_frame_callback_raises_on_stop(runtime: Runtime):
+    p = acquire.setup(runtime, "simulated.*empty", "Trash")
+    p.video[0].camera.settings.shape = (64, 48)
+    p.video[0].max_frame_count = 10
+    runtime.set_configuration(p)
+
+    def on_frames(frames):
+        raise ValueError("callback failed")
+
+    runtime.set_frame_callback(0, on_frames, batch=2)
+    runtime.start()
+    with pytest.raises(ValueError, match="callback failed"):
+        runtime.stop()
+    runtime.set_frame_callback(0, None)
+
+    # The frames passed to the callback were released even though it
+    # raised, and the rest were left queued.
+    with runtime.get_available_data(0) as packet:
+        frame_ids = [f.metadata().frame_id for f in packet.frames()]
+    assert frame_ids == list(range(2, p.video[0].max_frame_count))
+
+
+def test_set_frame_callback_error_is_per_stream(runtime: Runtime):
+    dm = runtime.device_manager()
+    p = runtime.get_configuration()
+    for stream in p.video:
+        stream.camera.identifier = dm.select(
+            DeviceKind.Camera, "simulated.*empty.*"
+        )
+        stream.storage.identifier = dm.select(DeviceKind.Storage, "Trash")
+        stream.camera.settings.shape = (64, 48)
+        stream.max_frame_count = 10
+    runtime.set_configuration(p)
+
+    def failing(frames):
+        raise ValueError("stream 0 failed")
+
+    frame_ids = []
+
+    def collecting(frames):
+        frame_ids.extend(f.metadata().frame_id for f in frames)
+
+    runtime.set_frame_callback(0, failing)
+    runtime.set_frame_callback(1, collecting)
+    runtime.start()
+    with pytest.raises(ValueError, match="stream 0 failed"):
+        runtime.stop()
+    runtime.set_frame_callback(0, None)
+    runtime.set_frame_callback(1, None)
+
+    # The failure on stream 0 didn't stop stream 1's callback.
+    assert frame_ids == list(range(p.video[1].max_frame_count))
+    with runtime.get_available_data(0) as packet:
+        assert packet.get_frame_count() == p.video[0].max_frame_count - 1
+
+
+def test_set_frame_callback_errors_are_raised_together(runtime: Runtime):
+    dm = runtime.device_manager()
+    p = runtime.get_configuration()
+    for stream in p.video:
+        stream.camera.identifier = dm.select(
+            DeviceKind.Camera, "simulated.*empty.*"
+        )
+        stream.storage.identifier = dm.select(DeviceKind.Storage, "Trash")
+        stream.camera.settings.shape = (64, 48)
+        stream.max_frame_count = 10
+    runtime.set_configuration(p)
+
+    def failing(stream_id: int):
+        def on_frames(frames):
+            raise ValueError(f"stream {stream_id} failed")
+
+        return on_frames
+
+    runtime.set_frame_callback(0, failing(0))
+    runtime.set_frame_callback(1, failing(1))
+    runtime.start()
+    with pytest.raises(Exception) as info:
+        runtime.stop()
+    if sys.version_info >= (3, 11):
+        messages = [str(e) for e in info.value.exceptions]
+    else:
+        messages = [str(info.value), str(info.value.__context__)]
+    assert messages == ["stream 0 failed", "stream 1 failed"]
+
+    # Both callbacks resumed, so nothing is left to raise.
+    runtime.stop()
+    runtime.set_frame_callback(0, None)
+    runtime.set_frame_callback(1, None)
+
+
+def test_set_frame_callback_removed_error_is_raised_on_stop(runtime: Runtime):
+    p = acquire.setup(runtime, "simulated.*empty", "Trash")
+    p.video[0].camera.settings.shape = (64, 48)
+    p.video[0].max_frame_count = 10
+    runtime.set_configuration(p)
+
+    def on_frames(frames):
+        raise ValueError("callback failed")
+
+    runtime.set_frame_callback(0, on_frames)
+    runtime.start()
+    runtime.wait_for_state(DeviceState.Armed, timeout_ms=20000)
+    time.sleep(0.1)
+    runtime.set_frame_callback(0, None)
+    with pytest.raises(ValueError, match="callback failed"):
+        runtime.stop()
+
+
+def test_set_frame_callback_from_callback(runtime: Runtime):
+    p = acquire.setup(runtime, "simulated.*empty", "Trash")
+    p.video[0].camera.settings.shape = (64, 48)
+    p.video[0].max_frame_count = 100
+    runtime.set_configuration(p)
+
+    frame_ids = []
+    done = threading.Event()
+
+    def second(frames):
+        frame_ids.extend(f.metadata().frame_id for f in frames)
+        if len(frame_ids) >= 10:
+            # Neither waits on the worker calling them.
+            runtime.set_frame_callback(0, None)
+            runtime.stop()
+            done.set()
+
+    def first(frames):
+        frame_ids.extend(f.metadata().frame_id for f in frames)
+        runtime.set_frame_callback(0, second)
+
+    runtime.set_frame_callback(0, first)
+    runtime.start()
+    assert done.wait(timeout=20)
+    assert frame_ids == list(range(len(frame_ids)))
+
+
+def test_set_frame_callback_resumes_after_read_error(runtime: Runtime):
+    p = acquire.setup(runtime, "simulated: radial sin", "Trash")
+    p.video[0].camera.settings.shape = (64, 48)
+    p.video[0].max_frame_count = 3
+    runtime.set_configuration(p)
+
+    runtime.start()
+    runtime.wait_for_state(DeviceState.Armed, timeout_ms=20000)
+
+    frame_ids = []
+
+    def collecting(frames):
+        frame_ids.extend(f.metadata().frame_id for f in frames)
+
+    # The open context keeps the stream mapped, so the callback's reads fail
+    # until it exits.
+    with runtime.get_available_data(0, peek=True):
+        runtime.set_frame_callback(0, collecting)
+        time.sleep(0.1)
+    with pytest.raises(acquire.StateError, match="already being read"):
+        runtime.stop()
+
+    # Raising the error resumed the callback.
+    runtime.stop()
+    runtime.set_frame_callback(0, None)
+    assert frame_ids == list(range(p.video[0].max_frame_count))
+
+
+def test_frame_data_without_copy(runtime: Runtime):
+    dm = runtime.device_manager()
+    p = runtime.get_configuration()
+    p.video[0].camera.identifier = dm.select(
+        DeviceKind.Camera, "simulated.*empty.*"
+    )
+    p.video[0].storage.identifier = dm.select(DeviceKind.Storage, "Trash")
+    p.video[0].camera.settings.shape = (64, 48)
+    p.video[0].camera.settings.pixel_type = acquire.SampleType.U8
+    p.video[0].camera.settings.input_triggers.frame_start = Trigger(
+        enable=True, line=0, edge="Rising"
+    )
+    p.video[0].max_frame_count = 2
+    runtime.set_configuration(p)
+
+    runtime.start()
+    runtime.execute_trigger(0)
+    with runtime.get_available_data(
+        0, min_frames=1, timeout_ms=5000
+    ) as packet:
+        assert packet.get_frame_count() == 1
+        frame = next(packet.frames())
+        view = frame.data(copy=False)
+        copied = frame.data()
+        assert not view.flags.writeable
+        assert not view.flags.owndata
+        assert isinstance(view.base, acquire.FrameDataGuard)
+        assert view.shape == copied.shape
+        assert view.dtype == copied.dtype
+        assert (view == copied).all()
+        with pytest.raises(ValueError):
+            view[0, 0, 0, 0] = 1
+
+    with pytest.raises(RuntimeError):
+        frame.data(copy=False)
+
+    # Reading the stream skips past the frame the view borrows, and its
+    # memory isn't reused meanwhile.
+    runtime.execute_trigger(0)
+    count, frame_id = wait_for_data(runtime, 0)
+    assert (count, frame_id) == (1, 1)
+    assert (view == copied).all()
+    assert runtime.get_stream_stats(0).frames_consumed == 2
+
+    del view
+    runtime.stop()
+
+
+def test_frame_data_without_copy_while_peeking(runtime: Runtime):
+    p = acquire.setup(runtime, "simulated: radial sin", "Trash")
+    p.video[0].camera.settings.shape = (64, 48)
+    p.video[0].max_frame_count = 3
+    runtime.set_configuration(p)
+
+    runtime.start()
+    runtime.wait_for_state(DeviceState.Armed, timeout_ms=20000)
+    with runtime.get_available_data(0, peek=True) as packet:
+        view = packet[0].data(copy=False)
+        copied = view.copy()
+
+    # The peeked frames are returned again while the view is alive.
+    with runtime.get_available_data(0) as packet:
+        assert packet.get_frame_count() == p.video[0].max_frame_count
+    with runtime.get_available_data(0) as packet:
+        assert packet.get_frame_count() == 0
+    assert (view == copied).all()
+
+    # Starting again could reuse the view's memory.
+    runtime.stop()
+    with pytest.raises(acquire.StateError, match="borrowed"):
+        runtime.start()
+    del view
+    runtime.start()
+    runtime.stop()
+
+
+def test_frame_dlpack(runtime: Runtime):
+    p = acquire.setup(runtime, "simulated: radial sin", "Trash")
+    p.video[0].camera.settings.shape = (64, 48)
+    p.video[0].max_frame_count = 3
+    runtime.set_configuration(p)
+
+    runtime.start()
+    with runtime.get_available_data(
+        0, min_frames=1, timeout_ms=5000
+    ) as packet:
+        frame = next(packet.frames())
+        expected = frame.data()
+        assert frame.__dlpack_device__() == (1, 0)
+
+        array = np.from_dlpack(frame)
+        assert array.shape == expected.shape
+        assert array.dtype == expected.dtype
+        assert (array == expected).all()
+
+        versioned = frame.__dlpack__(max_version=(1, 0))
+        assert "dltensor_versioned" in repr(versioned)
+        copied = frame.__dlpack__(max_version=(1, 0), copy=True)
+        assert "dltensor_versioned" in repr(copied)
+        legacy = frame.__dlpack__()
+        assert '"dltensor"' in repr(legacy)
+        with pytest.raises(BufferError):
+            frame.__dlpack__(copy=False)
+
+        buffer = memoryview(frame.data(copy=False))
+        assert buffer.readonly
+        assert buffer.shape == expected.shape
+        assert buffer.tobytes() == expected.tobytes()
+        buffer.release()
+    runtime.stop()
+
+
+def test_available_data_to_array(runtime: Runtime):
+    p = acquire.setup(runtime, "simulated: radial sin", "Trash")
+    p.video[0].camera.settings.shape = (64, 48)
+    p.video[0].max_frame_count = 5
+    runtime.set_configuration(p)
+
+    runtime.start()
+    with runtime.get_available_data(
+        0, min_frames=2, timeout_ms=5000
+    ) as packet:
+        frames = list(packet.frames())
+        stacked = packet.to_array()
+        assert stacked.shape == (len(frames), 1, 48, 64, 1)
+        assert (stacked == np.stack([f.data() for f in frames])).all()
+
+        table = packet.metadata_table()
+        assert list(table["frame_id"]) == [
+            f.metadata().frame_id for f in frames
+        ]
+        assert list(table["acq_thread"]) == [
+            f.metadata().timestamps.acq_thread for f in frames
+        ]
+        assert len(table["hardware"]) == len(frames)
+    runtime.stop()
+
+    assert packet.to_array().shape == (0, 0, 0, 0, 0)
+    assert len(packet.metadata_table()["frame_id"]) == 0
+
+
+def test_available_data_consume_and_peek(runtime: Runtime):
+    p = acquire.setup(runtime, "simulated.*empty", "Trash")
+    p.video[0].camera.settings.shape = (64, 48)
+    p.video[0].max_frame_count = 4
+    runtime.set_configuration(p)
+
+    def frame_ids(packet):
+        return [f.metadata().frame_id for f in packet.frames()]
+
+    runtime.start()
+    with runtime.get_available_data(
+        0, min_frames=4, timeout_ms=5000, peek=True
+    ) as packet:
+        assert frame_ids(packet) == [0, 1, 2, 3]
+
+    with runtime.get_available_data(0) as packet:
+        assert frame_ids(packet) == [0, 1, 2, 3]
+        with pytest.raises(ValueError):
+            packet.consume(5)
+        packet.consume(1)
+
+    with runtime.get_available_data(0, peek=True) as packet:
+        assert frame_ids(packet) == [1, 2, 3]
+        packet.consume(2)
+
+    with runtime.get_available_data(0) as packet:
+        assert frame_ids(packet) == [3]
+
+    with runtime.get_available_data(0) as packet:
+        assert packet.get_frame_count() == 0
+    runtime.stop()
+
+
+@pytest.fixture(scope="function")
+def u16_frame(runtime: Runtime):
+    # The first frame of a 64x48 16 bit acquisition, while it's mapped.
+    p = acquire.setup(runtime, "simulated: radial sin", "Trash")
+    p.video[0].camera.settings.shape = (64, 48)
+    p.video[0].camera.settings.pixel_type = acquire.SampleType.U16
+    p.video[0].max_frame_count = 3
+    runtime.set_configuration(p)
+
+    runtime.start()
+    with runtime.get_available_data(
+        0, min_frames=1, timeout_ms=5000
+    ) as packet:
+        yield next(packet.frames())
+    runtime.stop()
+
+
+def test_frame_raw_data(u16_frame: acquire.VideoFrame):
+    data = u16_frame.data()
+    raw = u16_frame.data(raw=True)
+    assert data.dtype == np.uint16
+    assert raw.dtype == np.uint8
+    assert raw.shape == (data.nbytes,)
+    assert raw.tobytes() == data.tobytes()
+    assert (u16_frame.data(raw=True, copy=False) == raw).all()
+
+
+def test_frame_shape_info(u16_frame: acquire.VideoFrame):
+    info = u16_frame.shape_info()
+    data = u16_frame.data()
+    assert info.dims == data.shape == (1, 48, 64, 1)
+    assert info.strides == (48 * 64, 64, 1, 1)
+    assert info.sample_type == acquire.SampleType.U16
+    assert not info.is_packed
+    assert info.nbytes == data.nbytes
+    assert tuple(info.dict()["dims"]) == (1, 48, 64, 1)
+
+
+def test_frame_metadata(u16_frame: acquire.VideoFrame):
+    metadata = u16_frame.metadata()
+    assert metadata.stream_id == 0
+    assert metadata.dims == (1, 48, 64, 1)
+    assert metadata.sample_type == acquire.SampleType.U16
+    assert metadata.bytes_of_frame > u16_frame.data().nbytes
+
+    d = metadata.dict()
+    assert d["frame_id"] == metadata.frame_id
+    assert d["stream_id"] == 0
+    assert json.loads(json.dumps(d))["bytes_of_frame"] == (
+        metadata.bytes_of_frame
+    )
+
+
+def test_clock_correlation(runtime: Runtime):
+    assert runtime.get_clock_correlation() is None
+    p = acquire.setup(runtime, "simulated: radial sin", "Trash")
+    p.video[0].camera.settings.shape = (64, 48)
+    p.video[0].max_frame_count = 3
+    runtime.set_configuration(p)
+
+    t0 = time.time()
+    runtime.start()
+    clock = runtime.get_clock_correlation()
+    assert clock is not None
+    assert abs(clock.wall_clock_ns * 1e-9 - t0) < 1
+    with runtime.get_available_data(
+        0, min_frames=1, timeout_ms=5000
+    ) as packet:
+        timestamps = next(packet.frames()).metadata().timestamps
+        elapsed = time.time() - t0
+        # The frame was acquired on the core's clock after the sample.
+        assert 0 <= clock.seconds_since_start(timestamps) <= elapsed
+        assert clock.to_monotonic(timestamps) <= time.monotonic()
+        acquired = clock.to_datetime(timestamps)
+        assert acquired.tzinfo is timezone.utc
+        assert abs(acquired.timestamp() - t0) <= elapsed + 1
+    runtime.stop()
+
+    copy = acquire.ClockCorrelation(**clock.dict())
+    assert copy.seconds_since_start(timestamps) == (
+        clock.seconds_since_start(timestamps)
+    )
+
+    # Conversions go through the core's clock reading at the start.
+    shifted = acquire.ClockCorrelation(**clock.dict())
+    shifted.acq_thread_ns -= 2_000_000_000
+    assert shifted.seconds_since_start(timestamps) == pytest.approx(
+        clock.seconds_since_start(timestamps) + 2
+    )
+    assert shifted.to_monotonic(timestamps) == pytest.approx(
+        clock.to_monotonic(timestamps) + 2
+    )
+    assert shifted.to_datetime(timestamps) > clock.to_datetime(timestamps)
+
+
+def test_detach_frames(runtime: Runtime):
+    p = acquire.setup(runtime, "simulated: radial sin", "Trash")
+    p.video[0].camera.settings.shape = (64, 48)
+    p.video[0].max_frame_count = 3
+    runtime.set_configuration(p)
+
+    runtime.start()
+    with runtime.get_available_data(
+        0, min_frames=2, timeout_ms=5000
+    ) as packet:
+        frames = list(packet.frames())
+        expected = [f.data() for f in frames]
+        ids = [f.metadata().frame_id for f in frames]
+        first = frames[0].detach()
+        detached = packet.detach_all()
+    runtime.stop()
+
+    with pytest.raises(RuntimeError):
+        frames[0].data()
+    assert len(detached) == len(expected)
+    for frame, data, frame_id in zip(detached, expected, ids):
+        assert frame.metadata().frame_id == frame_id
+        assert frame.metadata().stream_id == 0
+        assert np.array_equal(frame.data(), data)
+        assert frame.shape_info().dims == data.shape
+    view = first.data(copy=False)
+    assert view.base is first
+    assert not view.flags.writeable
+    assert np.array_equal(view, expected[0])
+    assert np.array_equal(np.from_dlpack(first), expected[0])
+
+
+def test_available_data_indexing(runtime: Runtime):
+    p = acquire.setup(runtime, "simulated: radial sin", "Trash")
+    p.video[0].camera.settings.shape = (64, 48)
+    p.video[0].max_frame_count = 4
+    runtime.set_configuration(p)
+
+    runtime.start()
+    with runtime.get_available_data(
+        0, min_frames=3, timeout_ms=5000
+    ) as packet:
+        ids = [f.metadata().frame_id for f in packet.frames()]
+        assert len(packet) == len(ids) == packet.get_frame_count()
+        assert packet[0].metadata().frame_id == ids[0]
+        assert packet[-1].metadata().frame_id == ids[-1]
+        assert [f.metadata().frame_id for f in packet[1:]] == ids[1:]
+        assert [f.metadata().frame_id for f in packet[::-2]] == ids[::-2]
+        assert packet[len(ids):] == []
+        with pytest.raises(IndexError):
+            packet[len(ids)]
+        with pytest.raises(IndexError):
+            packet[-len(ids) - 1]
+    runtime.stop()
+
+
+def test_read_stream_from_thread(runtime: Runtime):
+    dm = runtime.device_manager()
+    p = runtime.get_configuration()
+    p.video[0].camera.identifier = dm.select(
+        DeviceKind.Camera, "simulated.*empty.*"
+    )
+    p.video[0].storage.identifier = dm.select(DeviceKind.Storage, "Trash")
+    p.video[0].camera.settings.shape = (64, 48)
+    p.video[0].camera.settings.pixel_type = acquire.SampleType.U8
+    p.video[0].camera.settings.input_triggers.frame_start = Trigger(
+        enable=True, line=0, edge="Rising"
+    )
+    p.video[0].max_frame_count = 3
+    runtime.set_configuration(p)
+
+    waiting = threading.Event()
+    frame_ids: List[int] = []
+    statuses = []
+
+    def read_frames():
+        waiting.set()
+        with runtime.get_available_data(
+            0, min_frames=p.video[0].max_frame_count, timeout_ms=10000
+        ) as packet:
+            statuses.append(packet.wait_status)
+            frame_ids.extend(f.metadata().frame_id for f in packet)
+
+    runtime.start()
+    reader = threading.Thread(target=read_frames)
+    reader.start()
+    assert waiting.wait(timeout=5)
+    time.sleep(0.1)
+    # The reader is blocked waiting for frames that only arrive if this
+    # thread can trigger them while it waits.
+    assert reader.is_alive()
+    for _ in range(p.video[0].max_frame_count):
+        runtime.execute_trigger(0)
+        time.sleep(0.01)
+    reader.join(timeout=10)
+    runtime.stop()
+
+    assert not reader.is_alive()
+    assert statuses == [acquire.WaitStatus.Ready]
+    assert frame_ids == list(range(p.video[0].max_frame_count))
+
+
+def test_validate_configuration(runtime: Runtime):
+    p = acquire.setup(runtime, "simulated: radial sin", "Trash")
+    p = runtime.set_configuration(p)
+    assert runtime.validate_configuration(p) == []
+
+    caps = runtime.get_capabilities().video[0]
+    exposure = caps.camera.exposure_time_us
+    width = int(caps.camera.shape.x.high)
+    line_count = caps.camera.digital_lines.line_count
+    assert exposure.high > exposure.low and width > 0
+
+    camera = p.video[0].camera.settings
+    camera.exposure_time_us = exposure.high * 2
+    camera.offset = (0, 0)
+    camera.shape = (width + 1, camera.shape[1])
+    camera.input_triggers.frame_start = Trigger(
+        enable=True, line=line_count, edge="Rising"
+    )
+    p.video[0].storage.settings.acquisition_dimensions = [
+        acquire.StorageDimension(
+            name="x", kind="Space", array_size_px=64, chunk_size_px=64
+        )
+    ]
+
+    violations = runtime.validate_configuration(p)
+    found = [(v.field, v.kind) for v in violations]
+    kind = acquire.ViolationKind
+    settings = "video[0].camera.settings"
+    assert (f"{settings}.exposure_time_us", kind.OutOfRange) in found
+    assert (f"{settings}.shape[0]", kind.OutOfRange) in found
+    assert (
+        f"{settings}.input_triggers.frame_start.line",
+        kind.InvalidTriggerLine,
+    ) in found
+    assert (
+        "video[0].storage.settings.acquisition_dimensions[0].chunk_size_px",
+        kind.UnsupportedStorageFeature,
+    ) in found
+    assert all(v.stream_id == 0 and v.message for v in violations)
+
+
+def test_validate_configuration_pixel_type(runtime: Runtime):
+    p = acquire.setup(runtime, "simulated: radial sin", "Trash")
+    p = runtime.set_configuration(p)
+    supported = runtime.get_capabilities().video[0].camera
+    assert acquire.SampleType.U12 not in supported.supported_pixel_types
+
+    p.video[0].camera.settings.pixel_type = acquire.SampleType.U12
+    found = [(v.field, v.kind) for v in runtime.validate_configuration(p)]
+    assert found == [
+        (
+            "video[0].camera.settings.pixel_type",
+            acquire.ViolationKind.UnsupportedPixelType,
+        )
+    ]
+
+
+def test_validate_configuration_other_device(runtime: Runtime):
+    p = acquire.setup(runtime, "simulated: radial sin", "Trash")
+    p = runtime.set_configuration(p)
+
+    dm = runtime.device_manager()
+    p.video[0].camera.identifier = dm.select(
+        DeviceKind.Camera, "simulated: empty"
+    )
+    # Out of range for the configured camera, but not checked against it.
+    p.video[0].camera.settings.exposure_time_us = -1
+    found = [(v.field, v.kind) for v in runtime.validate_configuration(p)]
+    assert found == [
+        (
+            "video[0].camera.identifier",
+            acquire.ViolationKind.DeviceNotConfigured,
+        )
+    ]
+
+
+def test_validate_configuration_only_checks_configured_devices(
+    runtime: Runtime,
+):
+    p = acquire.setup(runtime, "simulated: radial sin", "Trash")
+    p = runtime.set_configuration(p)
+    before = runtime.get_configuration().dict()
+
+    # Neither device of stream 1 is configured, so only their identifiers
+    # are reported, whatever their settings.
+    dm = runtime.device_manager()
+    p.video[1].camera.identifier = dm.select(
+        DeviceKind.Camera, "simulated: empty"
+    )
+    p.video[1].camera.settings.exposure_time_us = -1
+    p.video[1].storage.identifier = dm.select(DeviceKind.Storage, "Trash")
+    found = [(v.field, v.kind) for v in runtime.validate_configuration(p)]
+    assert found == [
+        (
+            "video[1].camera.identifier",
+            acquire.ViolationKind.DeviceNotConfigured,
+        ),
+        (
+            "video[1].storage.identifier",
+            acquire.ViolationKind.DeviceNotConfigured,
+        ),
+    ]
+
+    # Validating didn't configure them.
+    assert runtime.get_configuration().dict() == before
+
+
+def test_set_configuration_report(runtime: Runtime):
+    p = acquire.setup(runtime, "simulated: radial sin", "Trash")
+    p.video[0].camera.settings.shape = (64, 48)
+    p = runtime.set_configuration(p)
+
+    # Applying an applied configuration changes nothing.
+    applied, changes = runtime.set_configuration(p, report=True)
+    assert changes == []
+    assert applied.video[0].camera.settings.shape == (64, 48)
+    assert isinstance(
+        runtime.set_configuration(p, strict=True), acquire.Properties
+    )
+
+    # Resolving a name pattern to the device it selects isn't an
+    # adjustment.
+    document = json.loads(p.to_json())
+    document["video"][0]["camera"]["identifier"]["name"] = "simulated.*sin"
+    p = acquire.Properties.from_json(json.dumps(document))
+    applied, changes = runtime.set_configuration(p, report=True)
+    assert changes == []
+    assert applied.video[0].camera.identifier.name == "simulated: radial sin"
+    assert isinstance(
+        runtime.set_configuration(p, strict=True), acquire.Properties
+    )
+
+    # The simulated cameras only keep the frame start trigger.
+    field = "video[0].camera.settings.input_triggers.exposure.enable"
+    p.video[0].camera.settings.input_triggers.exposure = Trigger(
+        enable=True, line=0, edge="Rising"
+    )
+    applied, changes = runtime.set_configuration(p, report=True)
+    assert (field, True, False) in [
+        (c.field, c.requested, c.applied) for c in changes
+    ]
+    assert not applied.video[0].camera.settings.input_triggers.exposure.enable
+
+    with pytest.raises(acquire.ConfigurationError) as exc_info:
+        runtime.set_configuration(p, strict=True)
+    err = exc_info.value
+    assert [c.field for c in err.changes] == [c.field for c in changes]
+    assert err.api == "acquire_configure"
+    assert err.stream_id is None
+    assert err.status_code is None
+    assert isinstance(err.messages, list)
+    # The adjusted configuration was applied regardless.
+    current = runtime.get_configuration()
+    assert not current.video[0].camera.settings.input_triggers.exposure.enable
+
+
+def test_properties_save_load(runtime: Runtime, tmp_path):
+    p = acquire.setup(runtime, "simulated: radial sin", "Trash")
+    p.video[0].camera.settings.shape = (64, 48)
+    p.video[0].max_frame_count = 7
+
+    for name in ("props.json", "props.toml"):
+        path = tmp_path / name
+        p.save(path)
+        loaded = acquire.Properties.load(str(path))
+        assert loaded.dict() == p.dict()
+
+    assert acquire.Properties.from_toml(p.to_toml()).dict() == p.dict()
+
+    # TOML integers are signed, so the largest frame counts only save as JSON.
+    p.video[0].max_frame_count = 2**64 - 1
+    with pytest.raises(ValueError, match=r"video\[0\]\.max_frame_count"):
+        p.to_toml()
+    with pytest.raises(ValueError, match="TOML"):
+        p.save(tmp_path / "large.toml")
+    assert not (tmp_path / "large.toml").exists()
+    p.save(tmp_path / "large.json")
+    loaded = acquire.Properties.load(str(tmp_path / "large.json"))
+    assert loaded.video[0].max_frame_count == 2**64 - 1
+    p.video[0].max_frame_count = 7
+
+    document = json.loads(p.to_json())
+    assert document["schema_version"] == 2
+
+    # Unversioned dicts saved by older releases still load.
+    legacy = acquire.Properties.from_json(json.dumps(p.dict()))
+    assert legacy.dict() == p.dict()
+
+    # Version 1 files may carry identifier ids, which are dropped.
+    v1 = json.loads(p.to_json())
+    v1["schema_version"] = 1
+    for device in ("camera", "storage"):
+        v1["video"][0][device]["identifier"]["id"] = [1, 2]
+    loaded = acquire.Properties.from_json(json.dumps(v1))
+    assert loaded.dict() == p.dict()
+    assert loaded.video[0].camera.identifier.id == (0, 0)
+
+    document["schema_version"] = 1000
+    with pytest.raises(ValueError):
+        acquire.Properties.from_json(json.dumps(document))
+
+
+def test_devices_resolved_by_name(runtime: Runtime, tmp_path):
+    dm = runtime.device_manager()
+    p = acquire.setup(runtime, "simulated: radial sin", "Trash")
+    camera = p.video[0].camera.identifier
+    assert camera is not None
+    assert "id" not in camera.dict()
+
+    path = tmp_path / "props.json"
+    p.save(path)
+    loaded = acquire.Properties.load(path, runtime=runtime)
+    assert loaded.video[0].camera.identifier == camera
+    assert loaded.video[0].storage.identifier == dm.select(
+        DeviceKind.Storage, "Trash"
+    )
+
+    # Saved identifiers carry no ids; set_configuration looks them up.
+    loaded = acquire.Properties.load(path)
+    applied = runtime.set_configuration(loaded)
+    assert applied.video[0].camera.identifier == camera
+
+    document = json.loads(p.to_json())
+    document["video"][0]["camera"]["identifier"]["name"] = "no such camera"
+    path.write_text(json.dumps(document))
+    with pytest.raises(acquire.DeviceNotFoundError, match="no such camera"):
+        acquire.Properties.load(path, runtime=runtime)
+    with pytest.raises(acquire.DeviceNotFoundError, match="video\\[0\\]"):
+        runtime.set_configuration(acquire.Properties.load(path))
+
+
+def test_devices_resolved_by_exact_name(runtime: Runtime):
+    dm = runtime.device_manager()
+    p = acquire.setup(runtime, "simulated: radial sin", "Trash")
+    document = json.loads(p.to_json())
+
+    def resolve(kind: str, name: str) -> str:
+        document["video"][0][kind]["identifier"]["name"] = name
+        loaded = acquire.Properties.from_json(json.dumps(document))
+        applied = runtime.set_configuration(loaded)
+        identifier = getattr(applied.video[0], kind).identifier
+        return identifier.name
+
+    # Each device is found by its own name, even where the name read as a
+    # regular expression would match another device or not match at all.
+    for device in dm.devices():
+        if device.kind == DeviceKind.Camera:
+            assert resolve("camera", device.name) == device.name
+        elif device.kind == DeviceKind.Storage:
+            assert resolve("storage", device.name) == device.name
+
+    # Names that aren't a device's are still taken as patterns.
+    assert resolve("camera", "simulated.*(sin|empty)$") in (
+        "simulated: radial sin",
+        "simulated: empty",
+    )
+    with pytest.raises(acquire.DeviceNotFoundError):
+        resolve("camera", "simulated: (radial")
+
+
+def test_devices_resolved_keep_ids(runtime: Runtime):
+    dm = runtime.device_manager()
+    p = acquire.setup(runtime, "simulated: radial sin", "Trash")
+    camera = dm.select(DeviceKind.Camera, "simulated: radial sin")
+    p.video[0].camera.identifier = camera
+    applied = runtime.set_configuration(p)
+    assert applied.video[0].camera.identifier == camera
+    assert applied.video[0].camera.identifier.id == camera.id
+
+
+def test_unknown_settings_rejected(runtime: Runtime):
+    with pytest.raises(ValueError, match="exposure_time_ms"):
+        acquire.CameraProperties(exposure_time_ms=5.0)
+    with pytest.raises(ValueError, match="enabel"):
+        Trigger(enabel=True)
+    assert not Trigger(enabel=True, strict=False).enable
+    with pytest.raises(ValueError, match="input_triggers.frame_start.enabel"):
+        acquire.CameraProperties(
+            input_triggers={"frame_start": {"enabel": True}}
+        )
+
+    p = acquire.setup(runtime, "simulated: radial sin", "Trash")
+    settings = "video\\[0\\].camera.settings"
+    d = p.dict()
+    d["video"][0]["camera"]["settings"]["exposure_time_ms"] = 5.0
+    with pytest.raises(ValueError, match=f"{settings}.exposure_time_ms"):
+        acquire.Properties(**d)
+    assert acquire.Properties(**d, strict=False).dict() == p.dict()
+    with pytest.raises(ValueError, match=f"{settings}.exposure_time_ms"):
+        acquire.Properties.from_json(json.dumps(d))
+    lenient = acquire.Properties.from_json(json.dumps(d), strict=False)
+    assert lenient.dict() == p.dict()
+
+    d = p.dict()
+    d["video"][0]["max_frame_count"] = "lots"
+    with pytest.raises(ValueError, match="video\\[0\\].max_frame_count"):
+        acquire.Properties(**d)
+
+
+def test_configuration_paths(runtime: Runtime):
+    p = acquire.setup(runtime, "simulated: radial sin", "Trash")
+    p.set_path("video[0].camera.settings.shape", (64, 48))
+    p.set_path("video[0].camera.settings.shape[1]", 32)
+    assert p.video[0].camera.settings.shape == (64, 32)
+    assert p.get_path("video[0].camera.settings.shape[0]") == 64
+
+    p.set_path("video[0]", {"max_frame_count": 11})
+    assert p.get_path("video[0].max_frame_count") == 11
+
+    with pytest.raises(ValueError, match="exposure_time_ms"):
+        p.set_path("video[0].camera.settings.exposure_time_ms", 5.0)
+    with pytest.raises(ValueError):
+        p.get_path("video[0]..camera")
+    with pytest.raises(TypeError, match="max_frame_count"):
+        p.set_path("video[0].max_frame_count", "lots")
+
+    runtime.set_configuration(p)
+    applied = runtime.update_configuration(
+        {
+            "video[0].max_frame_count": 13,
+            "video[0].camera.settings": {"shape": (32, 16)},
+        }
+    )
+    assert applied.video[0].max_frame_count == 13
+    assert applied.video[0].camera.settings.shape == (32, 16)
+    assert runtime.get_configuration().video[0].max_frame_count == 13
+
+
+def test_configuration_paths_through_copies(runtime: Runtime):
+    # Triggers and storage dimensions are returned as copies by their
+    # getters, so changes must be stored back.
+    p = acquire.setup(runtime, "simulated: radial sin", "Trash")
+    triggers = "video[0].camera.settings.input_triggers"
+    p.set_path(f"{triggers}.frame_start.enable", True)
+    frame_start = p.video[0].camera.settings.input_triggers.frame_start
+    assert frame_start.enable
+    p.set_path(triggers, {"frame_start": {"enable": False}})
+    assert not p.get_path(f"{triggers}.frame_start.enable")
+
+    dimensions = "video[0].storage.settings.acquisition_dimensions"
+    p.video[0].storage.settings.acquisition_dimensions = [
+        acquire.StorageDimension(
+            name=name, kind=kind, array_size_px=size, chunk_size_px=size
+        )
+        for name, kind, size in [("x", "Space", 64), ("y", "Space", 48)]
+    ]
+    p.set_path(f"{dimensions}[1].chunk_size_px", 16)
+    p.set_path(f"{dimensions}[0]", {"chunk_size_px": 32})
+    chunks = [
+        d.chunk_size_px
+        for d in p.video[0].storage.settings.acquisition_dimensions
+    ]
+    assert chunks == [32, 16]
+
+    runtime.set_configuration(p)
+    applied = runtime.update_configuration(
+        {
+            f"{triggers}.frame_start.enable": True,
+            f"{triggers}.frame_start": {"line": 0},
+        }
+    )
+    assert applied.video[0].camera.settings.input_triggers.frame_start.enable
+    applied = runtime.update_configuration(
+        {triggers: {"frame_start": {"enable": False}}}
+    )
+    frame_start = applied.video[0].camera.settings.input_triggers.frame_start
+    assert not frame_start.enable
+
+
+def test_get_available_data_times_out(runtime: Runtime):
+    dm = runtime.device_manager()
+    p = runtime.get_configuration()
+    p.video[0].camera.identifier = dm.select(
+        DeviceKind.Camera, "simulated.*empty.*"
+    )
+    p.video[0].storage.identifier = dm.select(DeviceKind.Storage, "Trash")
+    p.video[0].camera.settings.shape = (64, 48)
+    p.video[0].camera.settings.input_triggers.frame_start = Trigger(
+        enable=True, line=0, edge="Rising"
+    )
+    p.video[0].max_frame_count = 1
+    runtime.set_configuration(p)
+
+    runtime.start()
+    # No triggers, so no frames arrive before the timeout.
+    t0 = time.time()
+    with runtime.get_available_data(0, min_frames=1, timeout_ms=50) as packet:
+        assert packet.wait_status == acquire.WaitStatus.TimedOut
+        assert packet.get_frame_count() == 0
+    assert time.time() - t0 >= 0.05
+    runtime.abort()
+
+
+def test_stream_frames(runtime: Runtime):
+    p = acquire.setup(runtime, "simulated.*empty", "Trash")
+    p.video[0].camera.settings.shape = (64, 48)
+    p.video[0].max_frame_count = 5
+    runtime.set_configuration(p)
+
+    async def consume() -> List[int]:
+        frame_ids = []
+        async for frame in runtime.stream_frames(0):
+            assert frame.data().shape == (1, 48, 64, 1)
+            frame_ids.append(frame.metadata().frame_id)
+            if len(frame_ids) == p.video[0].max_frame_count:
+                break
+        return frame_ids
+
+    runtime.start()
+    frame_ids = asyncio.run(asyncio.wait_for(consume(), timeout=20))
+    runtime.stop()
+    assert frame_ids == list(range(p.video[0].max_frame_count))
+
+
+def test_stream_frames_before_start(runtime: Runtime):
+    p = acquire.setup(runtime, "simulated.*empty", "Trash")
+    p.video[0].camera.settings.shape = (64, 48)
+    p.video[0].max_frame_count = 5
+    runtime.set_configuration(p)
+
+    async def consume() -> List[int]:
+        frame_ids = []
+        async for frame in runtime.stream_frames(0):
+            frame_ids.append(frame.metadata().frame_id)
+            if len(frame_ids) == p.video[0].max_frame_count:
+                # The last frame of its region has been yielded, so the
+                # region is released and the stream can be read again.
+                with runtime.get_available_data(0, peek=True):
+                    pass
+        return frame_ids
+
+    async def main() -> List[int]:
+        # The stream exists before the runtime starts, and must not end
+        # just because the runtime isn't running yet.
+        task = asyncio.create_task(consume())
+        await asyncio.sleep(0.1)
+        assert not task.done()
+        runtime.start()
+        return await asyncio.wait_for(task, timeout=20)
+
+    frame_ids = asyncio.run(main())
+    runtime.stop()
+    assert frame_ids == list(range(p.video[0].max_frame_count))
+
+
 def wait_for_data(
     runtime: Runtime, stream_id: int = 0, timeout: Optional[timedelta] = None
 ) -> Tuple[int, int]:
@@ -1,2 +1,3 @@
 #include "acquire.h"
 #include "device/hal/device.manager.h"
+#include "platform.h"